@@ -0,0 +1,150 @@
+//! Headless benchmark of the parallel enemy hot loops at 50k enemies.
+//!
+//! Runs the real movement, animation, flip and collision detection systems once on a
+//! single-threaded task pool and once on the default pool, and reports the
+//! per-frame cost of each run and the speed-up:
+//!
+//! ```sh
+//! cargo run --release --example enemy_parallel_bench
+//! ```
+//!
+//! Bevy's task pools are process-wide, so each configuration runs in its own
+//! child process (`enemy_parallel_bench <threads>`).
+
+use std::{
+    env,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use bevy::{core::TaskPoolOptions, math::vec3, prelude::*};
+use rand::Rng;
+use z_attack_game::{
+    animation::{animate_enemy, flip_enemy_sprite_x, AnimationTimer},
+    biome::BiomeMap,
+    collision::{
+        detect_collisions, Collider, CollisionEnded, CollisionLayers, CollisionPairs,
+        CollisionStarted, SpatialGrid,
+    },
+    enemy::{update_enemy_transform, Enemy},
+    gun::Bullet,
+    lod::EnemyLod,
+    player::Player,
+    Tuning, BULLET_COLLIDER_RADIUS, ENEMY_COLLIDER_RADIUS, MAX_NUM_ENEMIES,
+};
+
+const FRAMES: u32 = 300;
+const WARMUP_FRAMES: u32 = 30;
+const NUM_BULLETS: u32 = 30;
+
+fn main() {
+    if let Some(threads) = env::args().nth(1) {
+        let threads: usize = threads.parse().expect("thread count");
+        let frame_time = run(threads);
+        println!("{}", frame_time.as_nanos());
+        return;
+    }
+
+    let default_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let sequential = run_child(1);
+    let parallel = run_child(default_threads);
+
+    println!("{MAX_NUM_ENEMIES} enemies, {NUM_BULLETS} bullets, {FRAMES} frames");
+    println!(
+        "1 thread:   {:.3}ms/frame",
+        sequential.as_secs_f64() * 1000.0
+    );
+    println!(
+        "{default_threads} threads: {:.3}ms/frame",
+        parallel.as_secs_f64() * 1000.0
+    );
+    println!(
+        "speed-up:   {:.2}x",
+        sequential.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
+
+fn run_child(threads: usize) -> Duration {
+    let output = Command::new(env::current_exe().unwrap())
+        .arg(threads.to_string())
+        .output()
+        .expect("failed to run benchmark child");
+    let nanos = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .expect("benchmark child output");
+
+    Duration::from_nanos(nanos)
+}
+
+fn run(threads: usize) -> Duration {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins.set(TaskPoolPlugin {
+        task_pool_options: TaskPoolOptions::with_num_threads(threads),
+    }))
+    .insert_resource(BiomeMap::new(42))
+    .init_resource::<Tuning>()
+    .add_event::<CollisionStarted>()
+    .add_event::<CollisionEnded>()
+    .init_resource::<CollisionPairs>()
+    .init_resource::<SpatialGrid>()
+    .add_systems(
+        Update,
+        (
+            update_enemy_transform,
+            animate_enemy,
+            flip_enemy_sprite_x,
+            detect_collisions,
+        ),
+    );
+
+    let world = app.world_mut();
+    world.spawn((Transform::default(), Player));
+
+    let mut rng = rand::rng();
+    for _ in 0..MAX_NUM_ENEMIES {
+        let pos = vec3(
+            rng.random_range(-2000.0..2000.0),
+            rng.random_range(-2000.0..2000.0),
+            10.0,
+        );
+        // A timer that has just finished so the animation path does real work every frame
+        let mut timer = Timer::from_seconds(0.08, TimerMode::Repeating);
+        timer.tick(Duration::from_secs_f32(0.08));
+        world.spawn((
+            Sprite::default(),
+            Transform::from_translation(pos),
+            Enemy::default(),
+            EnemyLod::OnScreen,
+            AnimationTimer(timer),
+            Collider::circle(ENEMY_COLLIDER_RADIUS).with_layers(
+                CollisionLayers::ENEMY,
+                CollisionLayers::PLAYER | CollisionLayers::BULLET,
+            ),
+        ));
+    }
+    for _ in 0..NUM_BULLETS {
+        let pos = vec3(
+            rng.random_range(-500.0..500.0),
+            rng.random_range(-500.0..500.0),
+            10.0,
+        );
+        world.spawn((
+            Transform::from_translation(pos),
+            Bullet,
+            Collider::circle(BULLET_COLLIDER_RADIUS)
+                .with_layers(CollisionLayers::BULLET, CollisionLayers::ENEMY),
+        ));
+    }
+
+    for _ in 0..WARMUP_FRAMES {
+        app.update();
+    }
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        app.update();
+    }
+
+    start.elapsed() / FRAMES
+}
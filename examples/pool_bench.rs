@@ -0,0 +1,188 @@
+//! Headless comparison of the pooled entity path against plain spawn/despawn.
+//!
+//! Simulates the gun and the enemy spawner at stress levels and reports
+//! entity allocation churn and frame-time spikes for both paths:
+//!
+//! ```sh
+//! cargo run --release --example pool_bench
+//! ```
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use z_attack_game::{
+    animation::AnimationTimer,
+    enemy::Enemy,
+    gun::{Bullet, BulletDirection, SpawnInstant},
+    pool::{BulletPool, EnemyPool},
+    SPRITE_SCALE_FACTOR,
+};
+
+const FRAMES: u32 = 3000;
+const WARMUP_FRAMES: u32 = 120;
+const BULLETS_PER_FRAME: u32 = 30;
+const BULLET_LIFE_FRAMES: u64 = 48;
+const ENEMY_BATCH: u32 = 100;
+const ENEMY_BATCH_INTERVAL: u64 = 60;
+const LIVE_ENEMIES: usize = 20_000;
+
+#[derive(Resource, Default)]
+struct Churn {
+    pooled: bool,
+    frame: u64,
+    bullets: VecDeque<(Entity, u64)>,
+    enemies: VecDeque<Entity>,
+    spawns: u64,
+    despawns: u64,
+}
+
+fn main() {
+    let spawn_despawn = run(false);
+    let pooled = run(true);
+
+    println!(
+        "{:<16}{:>12}{:>12}{:>12}{:>12}{:>12}{:>12}",
+        "path", "spawns", "despawns", "mean", "p99", "max", "hit rate"
+    );
+    for (name, report) in [("spawn/despawn", spawn_despawn), ("pooled", pooled)] {
+        println!(
+            "{:<16}{:>12}{:>12}{:>10.3}ms{:>10.3}ms{:>10.3}ms{:>11.1}%",
+            name,
+            report.spawns,
+            report.despawns,
+            report.mean.as_secs_f64() * 1000.0,
+            report.p99.as_secs_f64() * 1000.0,
+            report.max.as_secs_f64() * 1000.0,
+            report.hit_rate * 100.0,
+        );
+    }
+}
+
+struct Report {
+    spawns: u64,
+    despawns: u64,
+    mean: Duration,
+    p99: Duration,
+    max: Duration,
+    hit_rate: f32,
+}
+
+fn run(pooled: bool) -> Report {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(Churn {
+            pooled,
+            ..default()
+        })
+        .insert_resource(BulletPool::default())
+        .insert_resource(EnemyPool::default())
+        .add_systems(Update, (churn_bullets, churn_enemies));
+
+    let mut frame_times = Vec::with_capacity(FRAMES as usize);
+    for frame in 0..WARMUP_FRAMES + FRAMES {
+        let start = Instant::now();
+        app.update();
+        if frame >= WARMUP_FRAMES {
+            frame_times.push(start.elapsed());
+        }
+    }
+
+    frame_times.sort();
+    let churn = app.world().resource::<Churn>();
+    let bullet_metrics = app.world().resource::<BulletPool>().metrics;
+    let enemy_metrics = app.world().resource::<EnemyPool>().metrics;
+    let hits = bullet_metrics.hits + enemy_metrics.hits;
+    let misses = bullet_metrics.misses + enemy_metrics.misses;
+    let acquires = hits + misses;
+
+    Report {
+        // In the pooled path only pool misses allocate a new entity
+        spawns: if pooled { misses } else { churn.spawns },
+        despawns: churn.despawns,
+        mean: frame_times.iter().sum::<Duration>() / frame_times.len() as u32,
+        p99: frame_times[frame_times.len() * 99 / 100],
+        max: *frame_times.last().unwrap(),
+        hit_rate: if acquires == 0 {
+            0.0
+        } else {
+            hits as f32 / acquires as f32
+        },
+    }
+}
+
+fn churn_bullets(mut commands: Commands, mut churn: ResMut<Churn>, mut pool: ResMut<BulletPool>) {
+    churn.frame += 1;
+    let frame = churn.frame;
+
+    while churn
+        .bullets
+        .front()
+        .is_some_and(|(_, spawned)| frame - spawned > BULLET_LIFE_FRAMES)
+    {
+        let (entity, _) = churn.bullets.pop_front().unwrap();
+        if churn.pooled {
+            pool.release(&mut commands, entity);
+        } else {
+            commands.entity(entity).despawn();
+            churn.despawns += 1;
+        }
+    }
+
+    for i in 0..BULLETS_PER_FRAME {
+        let bundle = (
+            Sprite::default(),
+            Transform::from_xyz(i as f32, frame as f32, 10.0),
+            Bullet,
+            BulletDirection(Vec3::X),
+            SpawnInstant(Duration::ZERO),
+        );
+        let entity = if churn.pooled {
+            pool.acquire(&mut commands, bundle)
+        } else {
+            churn.spawns += 1;
+            commands.spawn(bundle).id()
+        };
+        churn.bullets.push_back((entity, frame));
+    }
+}
+
+fn churn_enemies(mut commands: Commands, mut churn: ResMut<Churn>, mut pool: ResMut<EnemyPool>) {
+    let batch = if churn.enemies.len() < LIVE_ENEMIES {
+        LIVE_ENEMIES as u32
+    } else if churn.frame.is_multiple_of(ENEMY_BATCH_INTERVAL) {
+        ENEMY_BATCH
+    } else {
+        return;
+    };
+
+    if churn.enemies.len() >= LIVE_ENEMIES {
+        for _ in 0..ENEMY_BATCH {
+            let entity = churn.enemies.pop_front().unwrap();
+            if churn.pooled {
+                pool.release(&mut commands, entity);
+            } else {
+                commands.entity(entity).despawn();
+                churn.despawns += 1;
+            }
+        }
+    }
+
+    for i in 0..batch {
+        let bundle = (
+            Sprite::default(),
+            Transform::from_xyz(i as f32, 0.0, 10.0).with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            Enemy::default(),
+            AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
+        );
+        let entity = if churn.pooled {
+            pool.acquire(&mut commands, bundle)
+        } else {
+            churn.spawns += 1;
+            commands.spawn(bundle).id()
+        };
+        churn.enemies.push_back(entity);
+    }
+}
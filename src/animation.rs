@@ -1,10 +1,12 @@
 use bevy::prelude::*;
 
 use crate::{
-    enemy::{self, Enemy},
+    death::Dying,
+    enemy::{Enemy, LiveEnemy},
     gun::Gun,
+    lod::EnemyLod,
     player::{Player, PlayerState},
-    CursorPosition, GameState,
+    CursorPosition, GameSet, GameState,
 };
 
 pub struct AnimationsPlugin;
@@ -18,12 +20,16 @@ impl Plugin for AnimationsPlugin {
             Update,
             (
                 animation_timer_tick,
-                animate_player,
-                animate_enemy,
-                flip_player_sprite_x,
-                flip_gun_sprite_y,
-                flip_enemy_sprite_x,
+                (
+                    animate_player,
+                    animate_enemy,
+                    flip_player_sprite_x,
+                    flip_gun_sprite_y,
+                    flip_enemy_sprite_x,
+                ),
             )
+                .chain()
+                .in_set(GameSet::Animation)
                 .run_if(in_state(GameState::InGame)),
         );
     }
@@ -31,9 +37,13 @@ impl Plugin for AnimationsPlugin {
 
 fn animation_timer_tick(
     time: Res<Time>,
-    mut query: Query<&mut AnimationTimer, With<AnimationTimer>>,
+    mut query: Query<(&mut AnimationTimer, Option<&EnemyLod>), With<AnimationTimer>>,
 ) {
-    for mut timer in query.iter_mut() {
+    for (mut timer, lod) in query.iter_mut() {
+        if lod.is_some_and(|lod| !lod.is_visible()) {
+            continue;
+        }
+
         timer.tick(time.delta());
     }
 }
@@ -57,18 +67,26 @@ fn animate_player(
     }
 }
 
-fn animate_enemy(mut enemy_query: Query<(&mut Sprite, &AnimationTimer), With<Enemy>>) {
+pub fn animate_enemy(
+    mut enemy_query: Query<(&mut Sprite, &AnimationTimer, &Enemy, &EnemyLod), Without<Dying>>,
+) {
     if enemy_query.is_empty() {
         return;
     }
 
-    for (mut sprite, timer) in enemy_query.iter_mut() {
-        if let Some(atlas) = &mut sprite.texture_atlas {
-            if timer.just_finished() {
-                atlas.index = 8 + (atlas.index + 1) % 4;
+    enemy_query
+        .par_iter_mut()
+        .for_each(|(mut sprite, timer, enemy, lod)| {
+            if !lod.is_visible() {
+                return;
             }
-        }
-    }
+
+            if let Some(atlas) = &mut sprite.texture_atlas {
+                if timer.just_finished() {
+                    atlas.index = enemy.kind.base_sprite_index() + (atlas.index + 1) % 4;
+                }
+            }
+        });
 }
 
 fn flip_player_sprite_x(
@@ -82,30 +100,28 @@ fn flip_player_sprite_x(
     let (mut sprite, transform) = player_query.single_mut();
 
     if let Some(cursor_position) = cursor_position.0 {
-        if cursor_position.x > transform.translation.x {
-            sprite.flip_x = false;
-        } else {
-            sprite.flip_x = true;
-        }
+        sprite.flip_x = cursor_position.x <= transform.translation.x;
     }
 }
 
-fn flip_enemy_sprite_x(
-    mut player_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<(&mut Sprite, &Transform), With<Enemy>>,
+pub fn flip_enemy_sprite_x(
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<(&mut Sprite, &Transform, &EnemyLod), LiveEnemy>,
 ) {
     if player_query.is_empty() || enemy_query.is_empty() {
         return;
     }
 
     let player_pos = player_query.single().translation;
-    for (mut sprite, enemy_tranform) in enemy_query.iter_mut() {
-        if enemy_tranform.translation.x < player_pos.x {
-            sprite.flip_x = false;
-        } else {
-            sprite.flip_x = true;
-        }
-    }
+    enemy_query
+        .par_iter_mut()
+        .for_each(|(mut sprite, enemy_tranform, lod)| {
+            if !lod.is_visible() {
+                return;
+            }
+
+            sprite.flip_x = enemy_tranform.translation.x >= player_pos.x;
+        });
 }
 
 fn flip_gun_sprite_y(
@@ -118,10 +134,6 @@ fn flip_gun_sprite_y(
 
     let (mut sprite, transform) = gun_query.single_mut();
     if let Some(cursor_position) = cursor_position.0 {
-        if cursor_position.y < transform.translation.x {
-            sprite.flip_y = false;
-        } else {
-            sprite.flip_y = true;
-        }
+        sprite.flip_y = cursor_position.y >= transform.translation.x;
     }
 }
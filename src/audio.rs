@@ -0,0 +1,405 @@
+//! Sound effects and music. There are no audio files yet, so every sound is
+//! synthesized into a sample buffer at startup and played through
+//! [`SynthSound`], a custom [`Decodable`] source.
+
+use std::{
+    f32::consts::TAU,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    audio::{AddAudioSource, Source, Volume},
+    prelude::*,
+    time::common_conditions::on_timer,
+    utils::HashMap,
+};
+use rand::Rng;
+
+use crate::{
+    enemy::LiveEnemy,
+    events::{BulletHit, EnemyKilled, GunFired, PickupCollected, PlayerDamaged},
+    player::Player,
+    settings::Settings,
+    *,
+};
+
+pub struct GameAudioPlugin;
+
+/// A mono sample buffer at [`AUDIO_SAMPLE_RATE`].
+#[derive(Asset, TypePath, Clone)]
+pub struct SynthSound {
+    samples: Arc<[f32]>,
+}
+
+pub struct SynthDecoder {
+    samples: Arc<[f32]>,
+    index: usize,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Sfx {
+    Gunshot,
+    Hit,
+    Groan,
+    Hurt,
+    Pickup,
+    UiClick,
+}
+
+/// Asks for a sound effect. `position` makes it fade with distance from the
+/// player; without one it plays at full volume.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaySfx {
+    pub sfx: Sfx,
+    pub position: Option<Vec2>,
+}
+
+#[derive(Resource)]
+struct SfxSounds {
+    sfx: HashMap<Sfx, Handle<SynthSound>>,
+    music: Handle<SynthSound>,
+}
+
+/// When each kind of effect last started.
+#[derive(Resource, Default)]
+struct SfxVoices(HashMap<Sfx, Instant>);
+
+/// A playing effect; the entity despawns when the sound ends.
+#[derive(Component)]
+struct SfxVoice(Sfx);
+
+#[derive(Component)]
+struct MusicTrack;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_source::<SynthSound>()
+            .add_event::<PlaySfx>()
+            .init_resource::<SfxVoices>()
+            .add_systems(Startup, (synthesize_sounds, start_music).chain())
+            .add_systems(
+                Update,
+                (
+                    (
+                        queue_gameplay_sfx,
+                        queue_enemy_groans
+                            .run_if(on_timer(Duration::from_secs_f32(AUDIO_GROAN_INTERVAL))),
+                    )
+                        .in_set(GameSet::Ui)
+                        .run_if(in_state(GameState::InGame)),
+                    // Menus play sounds too, so these run in every state
+                    play_sfx.after(GameSet::Ui),
+                    update_music_volume.run_if(resource_changed::<Settings>),
+                ),
+            );
+    }
+}
+
+impl Decodable for SynthSound {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthDecoder {
+            samples: self.samples.clone(),
+            index: 0,
+        }
+    }
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.samples.get(self.index).copied();
+        self.index += 1;
+        sample
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.len().saturating_sub(self.index))
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        AUDIO_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(
+            self.samples.len() as f32 / AUDIO_SAMPLE_RATE as f32,
+        ))
+    }
+}
+
+impl Sfx {
+    const ALL: [Sfx; 6] = [
+        Sfx::Gunshot,
+        Sfx::Hit,
+        Sfx::Groan,
+        Sfx::Hurt,
+        Sfx::Pickup,
+        Sfx::UiClick,
+    ];
+
+    pub fn at(self, position: Vec2) -> PlaySfx {
+        PlaySfx {
+            sfx: self,
+            position: Some(position),
+        }
+    }
+
+    pub fn global(self) -> PlaySfx {
+        PlaySfx {
+            sfx: self,
+            position: None,
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        match self {
+            Sfx::Gunshot => 0.5,
+            Sfx::Hit => 0.35,
+            Sfx::Groan => 0.5,
+            Sfx::Hurt => 0.7,
+            Sfx::Pickup => 0.6,
+            Sfx::UiClick => 0.5,
+        }
+    }
+
+    /// Voices of this kind allowed at once.
+    fn max_voices(&self) -> usize {
+        match self {
+            Sfx::Gunshot | Sfx::Groan | Sfx::Pickup => 3,
+            Sfx::Hit => 4,
+            Sfx::Hurt => 1,
+            Sfx::UiClick => 2,
+        }
+    }
+
+    /// Shortest gap between two starts of this kind, in seconds.
+    fn min_interval(&self) -> f32 {
+        match self {
+            Sfx::Gunshot => 0.06,
+            Sfx::Hit => 0.04,
+            Sfx::Groan => 0.25,
+            Sfx::Hurt => 0.2,
+            Sfx::Pickup => 0.05,
+            Sfx::UiClick => 0.02,
+        }
+    }
+
+    fn synthesize(&self) -> Vec<f32> {
+        let mut rng = rand::rng();
+        let mut noise = move || rng.random_range(-1.0..1.0);
+        match self {
+            Sfx::Gunshot => synthesize(0.2, |t| {
+                noise() * (-25.0 * t).exp() * 0.7 + (TAU * 80.0 * t).sin() * (-18.0 * t).exp() * 0.5
+            }),
+            Sfx::Hit => synthesize(0.06, |t| noise() * (-70.0 * t).exp() * 0.6),
+            Sfx::Groan => {
+                let mut phase = 0.0;
+                synthesize(0.7, |t| {
+                    // Sawtooth with a slow wobble in pitch
+                    phase = (phase
+                        + (85.0 + 10.0 * (TAU * 5.0 * t).sin()) / AUDIO_SAMPLE_RATE as f32)
+                        % 1.0;
+                    (phase * 2.0 - 1.0) * (std::f32::consts::PI * t / 0.7).sin() * 0.35
+                })
+            }
+            Sfx::Hurt => {
+                let mut phase = 0.0;
+                synthesize(0.25, |t| {
+                    phase = (phase + (300.0 - 720.0 * t) / AUDIO_SAMPLE_RATE as f32) % 1.0;
+                    let square = if phase < 0.5 { 1.0 } else { -1.0 };
+                    square * (-8.0 * t).exp() * 0.3
+                })
+            }
+            Sfx::Pickup => synthesize(0.18, |t| {
+                let freq = if t < 0.08 { 660.0 } else { 990.0 };
+                (TAU * freq * t).sin() * (-10.0 * t).exp() * 0.6
+            }),
+            Sfx::UiClick => synthesize(0.03, |t| (TAU * 1200.0 * t).sin() * (-150.0 * t).exp()),
+        }
+    }
+}
+
+/// Samples `sample(t)` over `duration` seconds, clipped to [-1, 1].
+fn synthesize(duration: f32, mut sample: impl FnMut(f32) -> f32) -> Vec<f32> {
+    let len = (duration * AUDIO_SAMPLE_RATE as f32) as usize;
+    (0..len)
+        .map(|i| sample(i as f32 / AUDIO_SAMPLE_RATE as f32).clamp(-1.0, 1.0))
+        .collect()
+}
+
+/// An eight second loop: a plucked bass line in A minor over a soft fifth
+/// drone, with hi-hat ticks on every eighth note.
+fn synthesize_music() -> Vec<f32> {
+    const BASS: [f32; 16] = [
+        110.0, 110.0, 130.81, 110.0, 98.0, 98.0, 87.31, 98.0, 110.0, 110.0, 130.81, 146.83, 130.81,
+        110.0, 98.0, 82.41,
+    ];
+    const BEAT: f32 = 0.5;
+
+    let mut rng = rand::rng();
+    synthesize(BEAT * BASS.len() as f32, |t| {
+        let beat = ((t / BEAT) as usize).min(BASS.len() - 1);
+        let in_beat = t - beat as f32 * BEAT;
+        let in_tick = t % (BEAT / 2.0);
+
+        // Triangle wave for the bass
+        let phase = (BASS[beat] * t).fract();
+        let bass = (4.0 * (phase - 0.5).abs() - 1.0) * (-4.0 * in_beat).exp() * 0.5;
+        let drone = ((TAU * 55.0 * t).sin() + (TAU * 82.41 * t).sin()) * 0.06;
+        let hat = rng.random_range(-1.0..1.0) * (-80.0 * in_tick).exp() * 0.08;
+        bass + drone + hat
+    })
+}
+
+fn synthesize_sounds(mut commands: Commands, mut sounds: ResMut<Assets<SynthSound>>) {
+    let mut add = |samples: Vec<f32>| {
+        sounds.add(SynthSound {
+            samples: samples.into(),
+        })
+    };
+
+    let sfx = Sfx::ALL
+        .into_iter()
+        .map(|sfx| (sfx, add(sfx.synthesize())))
+        .collect();
+    let music = add(synthesize_music());
+    commands.insert_resource(SfxSounds { sfx, music });
+}
+
+fn start_music(mut commands: Commands, sounds: Res<SfxSounds>, settings: Res<Settings>) {
+    commands.spawn((
+        AudioPlayer(sounds.music.clone()),
+        PlaybackSettings::LOOP.with_volume(Volume::new(settings.mixed_music_volume())),
+        MusicTrack,
+    ));
+}
+
+fn update_music_volume(settings: Res<Settings>, sink_query: Query<&AudioSink, With<MusicTrack>>) {
+    for sink in sink_query.iter() {
+        sink.set_volume(settings.mixed_music_volume());
+    }
+}
+
+fn queue_gameplay_sfx(
+    mut fired_events: EventReader<GunFired>,
+    mut hit_events: EventReader<BulletHit>,
+    mut killed_events: EventReader<EnemyKilled>,
+    mut damaged_events: EventReader<PlayerDamaged>,
+    mut collected_events: EventReader<PickupCollected>,
+    mut sfx_events: EventWriter<PlaySfx>,
+) {
+    sfx_events.send_batch(
+        fired_events
+            .read()
+            .map(|fired| Sfx::Gunshot.at(fired.position)),
+    );
+    sfx_events.send_batch(hit_events.read().map(|hit| Sfx::Hit.at(hit.position)));
+    sfx_events.send_batch(
+        killed_events
+            .read()
+            .map(|killed| Sfx::Groan.at(killed.position)),
+    );
+    sfx_events.send_batch(damaged_events.read().map(|_| Sfx::Hurt.global()));
+    sfx_events.send_batch(collected_events.read().map(|_| Sfx::Pickup.global()));
+}
+
+/// Now and then the nearest enemy groans, so the horde is heard coming.
+fn queue_enemy_groans(
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<&Transform, LiveEnemy>,
+    mut sfx_events: EventWriter<PlaySfx>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let nearest = enemy_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .min_by(|a, b| {
+            a.distance_squared(player_pos)
+                .total_cmp(&b.distance_squared(player_pos))
+        });
+    if let Some(position) = nearest {
+        sfx_events.send(Sfx::Groan.at(position));
+    }
+}
+
+/// Starts at most one voice per kind each frame, the loudest one asked for,
+/// and drops requests over the kind's voice limit or inside its minimum
+/// interval, so a thousand hits in a frame cost one sound.
+fn play_sfx(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    sounds: Res<SfxSounds>,
+    mut voices: ResMut<SfxVoices>,
+    mut sfx_events: EventReader<PlaySfx>,
+    voice_query: Query<&SfxVoice>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let listener = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+    let mut loudest = HashMap::<Sfx, f32>::new();
+    for event in sfx_events.read() {
+        let falloff = match (event.position, listener) {
+            (Some(position), Some(listener)) => {
+                1.0 - position.distance(listener) / AUDIO_FALLOFF_DISTANCE
+            }
+            _ => 1.0,
+        };
+        if falloff > 0.0 {
+            let gain = loudest.entry(event.sfx).or_default();
+            *gain = gain.max(falloff);
+        }
+    }
+    if loudest.is_empty() {
+        return;
+    }
+
+    let mut playing = HashMap::<Sfx, usize>::new();
+    for voice in voice_query.iter() {
+        *playing.entry(voice.0).or_default() += 1;
+    }
+    let mut total: usize = playing.values().sum();
+
+    let now = Instant::now();
+    let mut rng = rand::rng();
+    for (sfx, falloff) in loudest {
+        let recent = voices
+            .0
+            .get(&sfx)
+            .is_some_and(|started| (now - *started).as_secs_f32() < sfx.min_interval());
+        if recent
+            || total >= AUDIO_MAX_VOICES
+            || playing.get(&sfx).copied().unwrap_or_default() >= sfx.max_voices()
+        {
+            continue;
+        }
+
+        voices.0.insert(sfx, now);
+        total += 1;
+        let volume = settings.mixed_sfx_volume() * sfx.gain() * falloff;
+        let speed = 1.0 + rng.random_range(-AUDIO_PITCH_VARIATION..AUDIO_PITCH_VARIATION);
+        commands.spawn((
+            AudioPlayer(sounds.sfx[&sfx].clone()),
+            PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(volume))
+                .with_speed(speed),
+            SfxVoice(sfx),
+        ));
+    }
+}
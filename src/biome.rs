@@ -0,0 +1,273 @@
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{enemy::EnemyKind, player::Player, *};
+
+pub struct BiomePlugin;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum Biome {
+    #[default]
+    Meadow,
+    Desert,
+    Swamp,
+    Graveyard,
+}
+
+pub struct BiomeDef {
+    pub bg_color: (u8, u8, u8),
+    pub decorations: &'static [usize],
+    pub spawn_table: &'static [(EnemyKind, u32)],
+    pub speed_multiplier: f32,
+}
+
+/// Noise-driven lookup from a world position to the biome that covers it.
+///
+/// The noise is sampled once per ground tile up front, so the per-frame
+/// lookups for every moving enemy are a grid index. Positions off the map
+/// take the nearest edge tile.
+#[derive(Resource)]
+pub struct BiomeMap {
+    pub seed: u32,
+    /// One biome per [`BIOME_TILE_SIZE`] tile, row by row from the bottom left.
+    tiles: Vec<Biome>,
+    cols: usize,
+    rows: usize,
+}
+
+/// The biome the player is currently standing in.
+#[derive(Resource, Default)]
+pub struct CurrentBiome(pub Biome);
+
+#[derive(Component)]
+pub struct GroundTile;
+
+impl Plugin for BiomePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BiomeMap::default())
+            .init_resource::<CurrentBiome>()
+            .add_systems(OnEnter(GameState::GameInit), spawn_ground_tiles)
+            .add_systems(
+                Update,
+                (update_current_biome, update_background_color)
+                    .chain()
+                    .in_set(GameSet::Animation)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+const MEADOW: BiomeDef = BiomeDef {
+    bg_color: BG_COLOR,
+    decorations: &[24, 25],
+    spawn_table: &[(EnemyKind::Zombie, 8), (EnemyKind::Ghoul, 2)],
+    speed_multiplier: 1.0,
+};
+
+const DESERT: BiomeDef = BiomeDef {
+    bg_color: (222, 206, 164),
+    decorations: &[56],
+    spawn_table: &[
+        (EnemyKind::Ghoul, 6),
+        (EnemyKind::Slime, 3),
+        (EnemyKind::Zombie, 1),
+    ],
+    speed_multiplier: 1.0,
+};
+
+const SWAMP: BiomeDef = BiomeDef {
+    bg_color: (146, 166, 132),
+    decorations: &[24, 57],
+    spawn_table: &[(EnemyKind::Slime, 6), (EnemyKind::Zombie, 4)],
+    speed_multiplier: SWAMP_SPEED_MULTIPLIER,
+};
+
+const GRAVEYARD: BiomeDef = BiomeDef {
+    bg_color: (168, 166, 172),
+    decorations: &[58, 59],
+    spawn_table: &[
+        (EnemyKind::Zombie, 5),
+        (EnemyKind::Ghoul, 2),
+        (EnemyKind::Brute, 2),
+    ],
+    speed_multiplier: 1.0,
+};
+
+impl Biome {
+    pub fn def(&self) -> &'static BiomeDef {
+        match self {
+            Biome::Meadow => &MEADOW,
+            Biome::Desert => &DESERT,
+            Biome::Swamp => &SWAMP,
+            Biome::Graveyard => &GRAVEYARD,
+        }
+    }
+}
+
+impl BiomeDef {
+    pub fn bg_color(&self) -> Color {
+        Color::srgb_u8(self.bg_color.0, self.bg_color.1, self.bg_color.2)
+    }
+
+    pub fn random_decoration(&self, rng: &mut impl Rng) -> usize {
+        self.decorations[rng.random_range(0..self.decorations.len())]
+    }
+
+    pub fn random_enemy_kind(&self, rng: &mut impl Rng) -> EnemyKind {
+        let total: u32 = self.spawn_table.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.random_range(0..total);
+        for (kind, weight) in self.spawn_table {
+            if roll < *weight {
+                return *kind;
+            }
+            roll -= weight;
+        }
+
+        EnemyKind::default()
+    }
+}
+
+impl BiomeMap {
+    pub fn new(seed: u32) -> Self {
+        let cols = (WORLD_W * 2.0 / BIOME_TILE_SIZE).ceil() as usize;
+        let rows = (WORLD_H * 2.0 / BIOME_TILE_SIZE).ceil() as usize;
+        let tiles = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| sample_biome(tile_center(col, row), seed))
+            .collect();
+
+        Self {
+            seed,
+            tiles,
+            cols,
+            rows,
+        }
+    }
+
+    pub fn biome_at(&self, pos: Vec2) -> Biome {
+        let col = ((pos.x + WORLD_W) / BIOME_TILE_SIZE).floor() as isize;
+        let row = ((pos.y + WORLD_H) / BIOME_TILE_SIZE).floor() as isize;
+        let col = col.clamp(0, self.cols as isize - 1) as usize;
+        let row = row.clamp(0, self.rows as isize - 1) as usize;
+        self.tiles[row * self.cols + col]
+    }
+
+    pub fn speed_multiplier_at(&self, pos: Vec2) -> f32 {
+        self.biome_at(pos).def().speed_multiplier
+    }
+}
+
+fn tile_center(col: usize, row: usize) -> Vec2 {
+    vec2(
+        -WORLD_W + (col as f32 + 0.5) * BIOME_TILE_SIZE,
+        -WORLD_H + (row as f32 + 0.5) * BIOME_TILE_SIZE,
+    )
+}
+
+fn sample_biome(pos: Vec2, seed: u32) -> Biome {
+    let p = pos * BIOME_NOISE_SCALE;
+    let temperature = fbm(p, seed);
+    let moisture = fbm(p + Vec2::splat(57.0), seed.wrapping_add(1));
+
+    if moisture > 0.62 {
+        Biome::Swamp
+    } else if temperature > 0.62 {
+        Biome::Desert
+    } else if temperature < 0.38 && moisture < 0.45 {
+        Biome::Graveyard
+    } else {
+        Biome::Meadow
+    }
+}
+
+fn spawn_ground_tiles(mut commands: Commands, biome_map: Res<BiomeMap>) {
+    for row in 0..biome_map.rows {
+        for col in 0..biome_map.cols {
+            let Vec2 { x, y } = tile_center(col, row);
+            let biome = biome_map.tiles[row * biome_map.cols + col];
+
+            // Slight per-tile shading so large biomes don't read as a flat fill
+            let shade = 0.96 + value_noise(vec2(x, y) * 0.05, biome_map.seed) * 0.08;
+            let color = biome.def().bg_color().to_srgba();
+
+            commands.spawn((
+                Sprite {
+                    color: Color::srgb(color.red * shade, color.green * shade, color.blue * shade),
+                    custom_size: Some(Vec2::splat(BIOME_TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_translation(vec3(x, y, -1.0)),
+                GroundTile,
+            ));
+        }
+    }
+}
+
+fn update_current_biome(
+    biome_map: Res<BiomeMap>,
+    mut current_biome: ResMut<CurrentBiome>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+
+    let player_pos = player_query.single().translation.truncate();
+    let biome = biome_map.biome_at(player_pos);
+    if current_biome.0 != biome {
+        info!("Entered biome {:?}", biome);
+        current_biome.0 = biome;
+    }
+}
+
+fn update_background_color(
+    time: Res<Time>,
+    current_biome: Res<CurrentBiome>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    let target = current_biome.0.def().bg_color().to_linear();
+    let t = (BIOME_BG_LERP_SPEED * time.delta_secs()).min(1.0);
+    let color = clear_color.0.to_linear();
+
+    clear_color.0 = color.mix(&target, t).into();
+}
+
+fn hash(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(374_761_393)
+        ^ (y as u32).wrapping_mul(668_265_263)
+        ^ seed.wrapping_mul(2_246_822_519);
+    h = (h ^ (h >> 13)).wrapping_mul(1_274_126_177);
+    h ^= h >> 16;
+
+    h as f32 / u32::MAX as f32
+}
+
+/// Smooth noise in `[0, 1]`, one lattice cell per unit.
+pub(crate) fn value_noise(p: Vec2, seed: u32) -> f32 {
+    let cell = p.floor();
+    let (x, y) = (cell.x as i32, cell.y as i32);
+    let f = p - cell;
+    let u = f * f * (Vec2::splat(3.0) - 2.0 * f);
+
+    let a = hash(x, y, seed);
+    let b = hash(x + 1, y, seed);
+    let c = hash(x, y + 1, seed);
+    let d = hash(x + 1, y + 1, seed);
+
+    let bottom = a + (b - a) * u.x;
+    let top = c + (d - c) * u.x;
+    bottom + (top - bottom) * u.y
+}
+
+fn fbm(p: Vec2, seed: u32) -> f32 {
+    value_noise(p, seed) * 0.65 + value_noise(p * 2.0, seed.wrapping_add(7)) * 0.35
+}
+
+impl Default for BiomeMap {
+    fn default() -> Self {
+        Self::new(rand::rng().random())
+    }
+}
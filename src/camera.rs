@@ -1,22 +1,250 @@
-use bevy::math::vec3;
+use bevy::math::vec2;
 use bevy::prelude::*;
-use bevy_pancam::{PanCam, PanCamPlugin};
+use bevy_pancam::{DirectionKeys, PanCam, PanCamPlugin, PanCamSystemSet};
 
-use crate::{player::Player, GameState};
+use crate::{
+    biome::value_noise,
+    console::{ConsoleAppExt, ConsoleArgs, ConsoleResult},
+    events::{GunFired, PlayerDamaged},
+    player::Player,
+    progression::Wave,
+    settings::Settings,
+    *,
+};
 
 pub struct CameraPlugin;
 
+/// Who drives the camera. F2 switches between following the player and the
+/// free debug camera; [`PlayCinematic`] takes over until its path ends.
+#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum CameraMode {
+    #[default]
+    Follow,
+    /// Drag with the right or middle mouse button, zoom with the wheel.
+    FreePan,
+    Cinematic,
+}
+
+/// A point on a cinematic camera path, `time` seconds after it starts.
+#[derive(Debug, Clone, Copy)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub position: Vec2,
+    pub zoom: f32,
+}
+
+/// Plays a keyframed camera path, e.g. for a boss intro, then hands the
+/// camera back to the follow mode. Every [`CINEMATIC_WAVE_INTERVAL`]th wave
+/// opens with [`PlayCinematic::wave_intro`], as does the `cinematic` command.
+#[derive(Event, Debug, Clone)]
+pub struct PlayCinematic(pub Vec<CameraKeyframe>);
+
+#[derive(Resource)]
+struct CinematicPath {
+    keyframes: Vec<CameraKeyframe>,
+    elapsed: f32,
+}
+
+/// How the camera follows the player. The player can move inside the dead
+/// zone without moving the camera, the camera leads toward the cursor, and
+/// the view never leaves `bounds`.
+///
+/// `PanCam` only zooms while following: its drag and keyboard panning are off
+/// because they would fight the follow and share buttons with moving and firing.
+#[derive(Resource)]
+pub struct CameraFollow {
+    /// Half-size of the dead zone around the camera's focus.
+    pub dead_zone: Vec2,
+    pub look_ahead: f32,
+    pub max_look_ahead: f32,
+    /// Smoothing in seconds, independent of frame rate.
+    pub time_constant: f32,
+    pub bounds: Rect,
+    focus: Option<Vec2>,
+}
+
+/// Trauma-based screen shake. Gameplay adds trauma, which decays over time;
+/// the camera is offset by smooth noise scaled by trauma squared, so small
+/// knocks barely register and big ones shake hard.
+#[derive(Resource, Default)]
+pub struct CameraShake {
+    trauma: f32,
+    elapsed: f32,
+    /// Offset applied last frame, taken back off before following the player.
+    applied: Vec2,
+}
+
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(PanCamPlugin::default())
-            .add_systems(OnEnter(GameState::GameInit), (setup_camera,))
+        app.add_plugins(PanCamPlugin)
+            .init_state::<CameraMode>()
+            .add_event::<PlayCinematic>()
+            .add_console_command("cinematic", "cinematic", &[], cinematic_command)
+            .init_resource::<CameraFollow>()
+            .init_resource::<CameraShake>()
+            // Zoom first, so the follow clamps against this frame's view size
+            .configure_sets(Update, GameSet::Camera.after(PanCamSystemSet))
+            // No scrolling the view from behind the menus
+            .configure_sets(Update, PanCamSystemSet.run_if(in_state(GameState::InGame)))
+            .add_systems(Startup, setup_camera)
             .add_systems(
                 Update,
-                camera_follow_player.run_if(in_state(GameState::InGame)),
+                (
+                    toggle_free_camera.in_set(GameSet::Input),
+                    (
+                        configure_camera_mode.run_if(state_changed::<CameraMode>),
+                        play_wave_intros,
+                        start_cinematic,
+                        remove_camera_shake,
+                        camera_follow_player.run_if(in_state(CameraMode::Follow)),
+                        play_cinematic.run_if(in_state(CameraMode::Cinematic)),
+                        add_gameplay_trauma,
+                        apply_camera_shake,
+                    )
+                        .chain()
+                        .in_set(GameSet::Camera),
+                )
+                    .run_if(in_state(GameState::InGame)),
             );
     }
 }
 
+impl CameraMode {
+    /// Range the projection scale is kept in while in this mode.
+    pub fn zoom_limits(&self) -> (f32, f32) {
+        match self {
+            CameraMode::Follow => (CAMERA_MIN_ZOOM, CAMERA_MAX_ZOOM),
+            CameraMode::FreePan => (FREE_CAMERA_MIN_ZOOM, FREE_CAMERA_MAX_ZOOM),
+            CameraMode::Cinematic => (CINEMATIC_CAMERA_MIN_ZOOM, CINEMATIC_CAMERA_MAX_ZOOM),
+        }
+    }
+
+    /// `PanCam` settings for this mode. Following only lets it zoom, the free
+    /// camera gets drag too, and cinematics switch it off entirely.
+    fn pan_cam(&self) -> PanCam {
+        let (min_scale, max_scale) = self.zoom_limits();
+        let base = PanCam {
+            move_keys: DirectionKeys::NONE,
+            min_scale,
+            max_scale,
+            ..default()
+        };
+        match self {
+            CameraMode::Follow => PanCam {
+                grab_buttons: Vec::new(),
+                zoom_to_cursor: false,
+                ..base
+            },
+            CameraMode::FreePan => PanCam {
+                // Left click still fires
+                grab_buttons: vec![MouseButton::Right, MouseButton::Middle],
+                ..base
+            },
+            CameraMode::Cinematic => PanCam {
+                enabled: false,
+                ..base
+            },
+        }
+    }
+}
+
+impl PlayCinematic {
+    /// Pulls back from `center` to show the horde closing in, holds, then
+    /// returns to `zoom`.
+    pub fn wave_intro(center: Vec2, zoom: f32) -> Self {
+        let keyframe = |time, zoom| CameraKeyframe {
+            time,
+            position: center,
+            zoom,
+        };
+        Self(vec![
+            keyframe(0.0, zoom),
+            keyframe(1.2, CINEMATIC_WAVE_INTRO_ZOOM),
+            keyframe(2.4, CINEMATIC_WAVE_INTRO_ZOOM),
+            keyframe(3.2, zoom),
+        ])
+    }
+}
+
+impl CinematicPath {
+    /// Position and zoom `elapsed` seconds in, eased between keyframes.
+    fn sample(&self) -> Option<(Vec2, f32)> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > self.elapsed)?;
+        let to = self.keyframes[next];
+        let Some(from) = next.checked_sub(1).map(|i| self.keyframes[i]) else {
+            return Some((to.position, to.zoom));
+        };
+
+        let t = ((self.elapsed - from.time) / (to.time - from.time)).clamp(0.0, 1.0);
+        let t = t * t * (3.0 - 2.0 * t);
+        Some((
+            from.position.lerp(to.position, t),
+            from.zoom.lerp(to.zoom, t),
+        ))
+    }
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            dead_zone: vec2(CAMERA_DEAD_ZONE_W, CAMERA_DEAD_ZONE_H),
+            look_ahead: CAMERA_LOOK_AHEAD,
+            max_look_ahead: CAMERA_MAX_LOOK_AHEAD,
+            time_constant: CAMERA_FOLLOW_TIME_CONSTANT,
+            bounds: Rect::new(-WORLD_W, -WORLD_H, WORLD_W, WORLD_H),
+            focus: None,
+        }
+    }
+}
+
+impl CameraFollow {
+    /// Where the camera wants to be: the player, led toward the cursor and
+    /// held still while the player stays inside the dead zone.
+    fn update_focus(&mut self, player_pos: Vec2, cursor_pos: Option<Vec2>) -> Vec2 {
+        let lead = cursor_pos.map_or(Vec2::ZERO, |cursor| {
+            ((cursor - player_pos) * self.look_ahead).clamp_length_max(self.max_look_ahead)
+        });
+        let target = player_pos + lead;
+
+        let focus = self.focus.get_or_insert(target);
+        let excess = (target - *focus).abs() - self.dead_zone;
+        *focus += (target - *focus).signum() * excess.max(Vec2::ZERO);
+        *focus
+    }
+
+    /// Keeps a view of `half_size` inside the bounds, centring it on any
+    /// axis where the view is bigger than the world.
+    fn clamp(&self, center: Vec2, half_size: Vec2) -> Vec2 {
+        let min = self.bounds.min + half_size;
+        let max = self.bounds.max - half_size;
+        let clamp_axis = |value: f32, min: f32, max: f32| {
+            if min > max {
+                (min + max) / 2.0
+            } else {
+                value.clamp(min, max)
+            }
+        };
+        vec2(
+            clamp_axis(center.x, min.x, max.x),
+            clamp_axis(center.y, min.y, max.y),
+        )
+    }
+}
+
+impl CameraShake {
+    /// Adds `amount` of trauma, saturating at 1.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+}
+
 fn setup_camera(mut commands: Commands) {
     commands
         .spawn((
@@ -25,20 +253,196 @@ fn setup_camera(mut commands: Commands) {
                 ..Default::default()
             },
         ))
-        .insert(PanCam::default());
+        .insert(CameraMode::default().pan_cam());
+}
+
+fn toggle_free_camera(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    camera_mode: Res<State<CameraMode>>,
+    mut next_camera_mode: ResMut<NextState<CameraMode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F2) {
+        return;
+    }
+
+    match camera_mode.get() {
+        CameraMode::Follow => next_camera_mode.set(CameraMode::FreePan),
+        CameraMode::FreePan => next_camera_mode.set(CameraMode::Follow),
+        CameraMode::Cinematic => {}
+    }
+}
+
+fn configure_camera_mode(
+    camera_mode: Res<State<CameraMode>>,
+    mut follow: ResMut<CameraFollow>,
+    mut camera_query: Query<(&mut PanCam, &mut OrthographicProjection)>,
+) {
+    let Ok((mut pan_cam, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let mode = camera_mode.get();
+    let (min_zoom, max_zoom) = mode.zoom_limits();
+    *pan_cam = mode.pan_cam();
+    projection.scale = projection.scale.clamp(min_zoom, max_zoom);
+    // Re-centre the dead zone rather than snapping back to a stale focus
+    follow.focus = None;
+}
+
+fn start_cinematic(
+    mut commands: Commands,
+    mut cinematic_events: EventReader<PlayCinematic>,
+    mut next_camera_mode: ResMut<NextState<CameraMode>>,
+) {
+    let Some(PlayCinematic(keyframes)) = cinematic_events.read().last() else {
+        return;
+    };
+    if keyframes.is_empty() {
+        return;
+    }
+
+    commands.insert_resource(CinematicPath {
+        keyframes: keyframes.clone(),
+        elapsed: 0.0,
+    });
+    next_camera_mode.set(CameraMode::Cinematic);
+}
+
+fn play_wave_intros(
+    wave: Res<Wave>,
+    mut shown: Local<u32>,
+    camera_mode: Res<State<CameraMode>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut cinematic_events: EventWriter<PlayCinematic>,
+) {
+    // The timer ticks every frame, the wave number only changes once a wave
+    if *shown == wave.number {
+        return;
+    }
+    *shown = wave.number;
+    if wave.number == 1
+        || !wave.number.is_multiple_of(CINEMATIC_WAVE_INTERVAL)
+        || *camera_mode.get() != CameraMode::Follow
+    {
+        return;
+    }
+
+    if let Ok((transform, projection)) = camera_query.get_single() {
+        cinematic_events.send(PlayCinematic::wave_intro(
+            transform.translation.truncate(),
+            projection.scale,
+        ));
+    }
+}
+
+fn cinematic_command(
+    In(_): In<ConsoleArgs>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut cinematic_events: EventWriter<PlayCinematic>,
+) -> ConsoleResult {
+    let (transform, projection) = camera_query.get_single().map_err(|_| "no camera")?;
+    cinematic_events.send(PlayCinematic::wave_intro(
+        transform.translation.truncate(),
+        projection.scale,
+    ));
+    Ok("playing wave intro".to_string())
+}
+
+fn play_cinematic(
+    mut commands: Commands,
+    time: Res<Time>,
+    path: Option<ResMut<CinematicPath>>,
+    mut next_camera_mode: ResMut<NextState<CameraMode>>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+) {
+    let Some(mut path) = path else {
+        return;
+    };
+    path.elapsed += time.delta_secs();
+
+    let Some((position, zoom)) = path.sample() else {
+        commands.remove_resource::<CinematicPath>();
+        next_camera_mode.set(CameraMode::Follow);
+        return;
+    };
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let (min_zoom, max_zoom) = CameraMode::Cinematic.zoom_limits();
+    transform.translation = position.extend(transform.translation.z);
+    projection.scale = zoom.clamp(min_zoom, max_zoom);
 }
 
 fn camera_follow_player(
+    time: Res<Time>,
+    cursor_position: Res<CursorPosition>,
+    mut follow: ResMut<CameraFollow>,
     player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), Without<Player>>,
 ) {
     if camera_query.is_empty() || player_query.is_empty() {
         return;
     }
 
-    let mut camera_transform = camera_query.single_mut();
-    let player_transform = player_query.single().translation;
-    let (x, y) = (player_transform.x, player_transform.y);
+    let (mut camera_transform, projection) = camera_query.single_mut();
+    let player_pos = player_query.single().translation.truncate();
+    let focus = follow.update_focus(player_pos, cursor_position.0);
+
+    let camera_pos = camera_transform.translation.truncate();
+    let blend = 1.0 - (-time.delta_secs() / follow.time_constant).exp();
+    let camera_pos = follow.clamp(camera_pos.lerp(focus, blend), projection.area.half_size());
+    camera_transform.translation = camera_pos.extend(camera_transform.translation.z);
+}
+
+fn add_gameplay_trauma(
+    mut camera_shake: ResMut<CameraShake>,
+    mut fired_events: EventReader<GunFired>,
+    mut damaged_events: EventReader<PlayerDamaged>,
+) {
+    for _ in fired_events.read() {
+        camera_shake.add_trauma(GUN_FIRE_TRAUMA);
+    }
+    for _ in damaged_events.read() {
+        camera_shake.add_trauma(PLAYER_HIT_TRAUMA);
+    }
+}
+
+fn remove_camera_shake(
+    mut camera_shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+) {
+    if let Ok(mut transform) = camera_query.get_single_mut() {
+        transform.translation -= camera_shake.applied.extend(0.0);
+    }
+    camera_shake.applied = Vec2::ZERO;
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut camera_shake: ResMut<CameraShake>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let dt = time.delta_secs();
+    camera_shake.elapsed += dt;
+    let shake = camera_shake.trauma * camera_shake.trauma * settings.screen_shake;
+    camera_shake.trauma = (camera_shake.trauma - CAMERA_SHAKE_DECAY * dt).max(0.0);
+
+    let Ok((mut transform, projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+    if shake <= 0.0 {
+        return;
+    }
 
-    camera_transform.translation = camera_transform.translation.lerp(vec3(x, y, 1.0), 0.1);
+    let t = camera_shake.elapsed * CAMERA_SHAKE_FREQUENCY;
+    let noise = vec2(
+        value_noise(vec2(t, 0.0), 1) * 2.0 - 1.0,
+        value_noise(vec2(0.0, t), 2) * 2.0 - 1.0,
+    );
+    // In screen pixels, so zooming out doesn't make it look calmer
+    let offset = noise * CAMERA_SHAKE_MAX_OFFSET * shake * projection.scale;
+    transform.translation += offset.extend(0.0);
+    camera_shake.applied = offset;
 }
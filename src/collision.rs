@@ -1,40 +1,505 @@
-use bevy::prelude::*;
+use std::ops::BitOr;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet, Parallel},
+};
+use rand::Rng;
 
 use crate::{
-    enemy::{self, Enemy},
-    gun::Bullet,
+    events::BulletHit,
+    gun::{Bullet, BulletDirection, Shooter},
+    pickups::PowerUps,
     player::Player,
-    GameState, BULLET_DAMAGE,
+    pool::BulletPool,
+    *,
 };
 
 pub struct CollisionPlugin;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColliderShape {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
+}
+
+/// Bit set of collision layers. A collider's `layers` say what it is, its
+/// `mask` says what it wants to touch; a pair collides only when both agree.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct CollisionLayers(pub u32);
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub layers: CollisionLayers,
+    pub mask: CollisionLayers,
+}
+
+/// Sent on the first frame two colliders overlap. Entities are ordered so `0 < 1`.
+#[derive(Event, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CollisionStarted(pub Entity, pub Entity);
+
+/// Sent on the first frame two colliders stop overlapping, or one of them loses its collider.
+#[derive(Event, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CollisionEnded(pub Entity, pub Entity);
+
+/// Position at the end of the previous frame, for swept tests on fast movers.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PreviousPosition(pub Vec2);
+
+/// Pairs that are overlapping this frame.
+#[derive(Resource, Default)]
+pub struct CollisionPairs(HashSet<(Entity, Entity)>);
+
+/// Uniform grid of collider centers, bucketed per layer so a collider only
+/// visits cells of the layers in its mask.
+#[derive(Resource, Default)]
+pub struct SpatialGrid {
+    cells: HashMap<(u32, IVec2), Vec<usize>>,
+    entries: Vec<(Entity, Vec2, Collider)>,
+    layer_counts: [u32; 32],
+    layer_max_half_extents: [Vec2; 32],
+}
+
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .init_resource::<CollisionPairs>()
+            .init_resource::<SpatialGrid>()
+            .add_systems(
+                Update,
+                handle_bullet_hits
+                    .in_set(GameSet::Collision)
+                    .run_if(in_state(GameState::InGame)),
+            );
+
+        // The lightweight grid is the default, the physics backend is for
+        // scenes that want pushing rather than the 50k-enemy stress mode
+        #[cfg(not(feature = "physics"))]
         app.add_systems(
             Update,
-            (handle_enemy_bullet_collision).run_if(in_state(GameState::InGame)),
+            (
+                rebuild_spatial_grid.before(handle_bullet_hits),
+                detect_collisions.after(rebuild_spatial_grid),
+            )
+                .in_set(GameSet::Collision)
+                .run_if(in_state(GameState::InGame)),
         );
+        #[cfg(feature = "physics")]
+        app.add_plugins(crate::physics::PhysicsBackendPlugin);
     }
 }
 
-fn handle_enemy_bullet_collision(
-    bullet_query: Query<&Transform, With<Bullet>>,
-    mut enemy_query: Query<(&Transform, &mut Enemy), With<Enemy>>,
-) {
-    if bullet_query.is_empty() || enemy_query.is_empty() {
-        return;
-    }
-
-    for bullet_transform in bullet_query.iter() {
-        for (enemy_transform, mut enemy) in enemy_query.iter_mut() {
-            if bullet_transform
-                .translation
-                .distance_squared(enemy_transform.translation)
-                <= 1000.0
-            {
-                enemy.health -= BULLET_DAMAGE;
+impl CollisionLayers {
+    pub const NONE: Self = Self(0);
+    pub const PLAYER: Self = Self(1 << 0);
+    pub const ENEMY: Self = Self(1 << 1);
+    pub const BULLET: Self = Self(1 << 2);
+    pub const PICKUP: Self = Self(1 << 3);
+    pub const OBSTACLE: Self = Self(1 << 4);
+
+    pub fn intersects(&self, other: CollisionLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// These layers with every layer in `other` cleared.
+    pub fn without(self, other: CollisionLayers) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    fn bits(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..u32::BITS).filter(|bit| self.0 & (1 << bit) != 0)
+    }
+}
+
+impl BitOr for CollisionLayers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Collider {
+    /// Circle collider, `radius` in sprite pixels.
+    pub fn circle(radius: f32) -> Self {
+        Self {
+            shape: ColliderShape::Circle {
+                radius: radius * SPRITE_SCALE_FACTOR,
+            },
+            layers: CollisionLayers::NONE,
+            mask: CollisionLayers::NONE,
+        }
+    }
+
+    /// Axis-aligned box collider, `half_extents` in sprite pixels.
+    pub fn aabb(half_extents: Vec2) -> Self {
+        Self {
+            shape: ColliderShape::Aabb {
+                half_extents: half_extents * SPRITE_SCALE_FACTOR,
+            },
+            layers: CollisionLayers::NONE,
+            mask: CollisionLayers::NONE,
+        }
+    }
+
+    pub fn with_layers(mut self, layers: CollisionLayers, mask: CollisionLayers) -> Self {
+        self.layers = layers;
+        self.mask = mask;
+        self
+    }
+
+    pub fn interacts_with(&self, other: &Collider) -> bool {
+        self.mask.intersects(other.layers) && other.mask.intersects(self.layers)
+    }
+
+    pub fn half_extents(&self) -> Vec2 {
+        match self.shape {
+            ColliderShape::Circle { radius } => Vec2::splat(radius),
+            ColliderShape::Aabb { half_extents } => half_extents,
+        }
+    }
+}
+
+impl ColliderShape {
+    pub fn overlaps(&self, pos: Vec2, other: &ColliderShape, other_pos: Vec2) -> bool {
+        match (*self, *other) {
+            (ColliderShape::Circle { radius: a }, ColliderShape::Circle { radius: b }) => {
+                pos.distance_squared(other_pos) <= (a + b) * (a + b)
+            }
+            (ColliderShape::Aabb { half_extents: a }, ColliderShape::Aabb { half_extents: b }) => {
+                let delta = (pos - other_pos).abs();
+                delta.x <= a.x + b.x && delta.y <= a.y + b.y
+            }
+            (ColliderShape::Circle { radius }, ColliderShape::Aabb { half_extents }) => {
+                circle_overlaps_aabb(pos, radius, other_pos, half_extents)
             }
+            (ColliderShape::Aabb { half_extents }, ColliderShape::Circle { radius }) => {
+                circle_overlaps_aabb(other_pos, radius, pos, half_extents)
+            }
+        }
+    }
+}
+
+fn circle_overlaps_aabb(center: Vec2, radius: f32, box_center: Vec2, half_extents: Vec2) -> bool {
+    let closest = center.clamp(box_center - half_extents, box_center + half_extents);
+    center.distance_squared(closest) <= radius * radius
+}
+
+/// Fraction `t` in `[0, 1]` along `start -> end` at which a circle of `radius`
+/// moving along the segment first touches `shape` at `pos`, if it does.
+pub fn sweep_circle(
+    start: Vec2,
+    end: Vec2,
+    radius: f32,
+    shape: &ColliderShape,
+    pos: Vec2,
+) -> Option<f32> {
+    match *shape {
+        ColliderShape::Circle { radius: other } => {
+            segment_circle_toi(start, end, pos, radius + other)
         }
+        // Minkowski sum approximated as the box grown by the radius on each side
+        ColliderShape::Aabb { half_extents } => {
+            segment_aabb_toi(start, end, pos, half_extents + Vec2::splat(radius))
+        }
+    }
+}
+
+fn segment_circle_toi(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = start - center;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        // Already overlapping at the start of the sweep
+        return Some(0.0);
+    }
+
+    let delta = end - start;
+    let a = delta.length_squared();
+    let b = offset.dot(delta);
+    if a == 0.0 || b >= 0.0 {
+        return None;
+    }
+
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / a;
+    (t <= 1.0).then_some(t)
+}
+
+fn segment_aabb_toi(start: Vec2, end: Vec2, center: Vec2, half_extents: Vec2) -> Option<f32> {
+    let min = center - half_extents;
+    let max = center + half_extents;
+    let delta = end - start;
+    let (mut t_min, mut t_max) = (0.0_f32, 1.0_f32);
+
+    for axis in 0..2 {
+        if delta[axis] == 0.0 {
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let inv = 1.0 / delta[axis];
+        let t0 = (min[axis] - start[axis]) * inv;
+        let t1 = (max[axis] - start[axis]) * inv;
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+        if t_min > t_max {
+            return None;
+        }
+    }
+
+    Some(t_min)
+}
+
+impl CollisionPairs {
+    pub fn contains(&self, a: Entity, b: Entity) -> bool {
+        self.0.contains(&ordered(a, b))
+    }
+
+    /// Returns `true` if the pair was not already touching.
+    #[cfg(feature = "physics")]
+    pub(crate) fn insert(&mut self, a: Entity, b: Entity) -> bool {
+        self.0.insert(ordered(a, b))
+    }
+
+    /// Returns `true` if the pair was touching.
+    #[cfg(feature = "physics")]
+    pub(crate) fn remove(&mut self, a: Entity, b: Entity) -> bool {
+        self.0.remove(&ordered(a, b))
+    }
+}
+
+impl SpatialGrid {
+    pub fn cell_size(&self) -> f32 {
+        COLLISION_GRID_CELL_SIZE
+    }
+
+    /// Occupied cells, for debug drawing.
+    pub fn occupied_cells(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.cells
+            .iter()
+            .filter(|(_, entries)| !entries.is_empty())
+            .map(|((_, cell), _)| *cell)
+    }
+
+    fn clear(&mut self) {
+        // Keep allocations for cells that were used last frame, most are reused next frame
+        self.cells.retain(|_, entries| {
+            let keep = !entries.is_empty();
+            entries.clear();
+            keep
+        });
+        self.entries.clear();
+        self.layer_counts = [0; 32];
+        self.layer_max_half_extents = [Vec2::ZERO; 32];
+    }
+
+    fn insert(&mut self, entity: Entity, pos: Vec2, collider: Collider) {
+        let index = self.entries.len();
+        let cell = (pos / COLLISION_GRID_CELL_SIZE).floor().as_ivec2();
+        self.entries.push((entity, pos, collider));
+        for bit in collider.layers.bits() {
+            self.cells.entry((bit, cell)).or_default().push(index);
+            self.layer_counts[bit as usize] += 1;
+            let max_half_extents = &mut self.layer_max_half_extents[bit as usize];
+            *max_half_extents = max_half_extents.max(collider.half_extents());
+        }
+    }
+
+    /// Whether colliders on layer `from` should look for layer `to`. Only the
+    /// less populated side of a layer pair queries, so a few bullets search the
+    /// enemy cells instead of every enemy searching the bullet cells.
+    fn should_query(&self, from: u32, to: u32) -> bool {
+        (self.layer_counts[from as usize], from) <= (self.layer_counts[to as usize], to)
+    }
+
+    /// Earliest collider hit by `collider` moving from `start` to `end`, as the
+    /// entity and the fraction of the path travelled. Ties go to the lower entity.
+    pub fn sweep(
+        &self,
+        entity: Entity,
+        collider: &Collider,
+        start: Vec2,
+        end: Vec2,
+    ) -> Option<(Entity, f32)> {
+        let radius = collider.half_extents().max_element();
+        let center = (start + end) * 0.5;
+        let half_extents = (end - start).abs() * 0.5 + Vec2::splat(radius);
+
+        let mut earliest: Option<(f32, Entity)> = None;
+        for bit in collider.mask.bits() {
+            for index in self.query(bit, center, half_extents) {
+                let (other, other_pos, other_collider) = self.entries[index];
+                if other == entity || !collider.interacts_with(&other_collider) {
+                    continue;
+                }
+
+                if let Some(t) = sweep_circle(start, end, radius, &other_collider.shape, other_pos)
+                {
+                    if earliest.is_none_or(|hit| (t, other) < hit) {
+                        earliest = Some((t, other));
+                    }
+                }
+            }
+        }
+
+        earliest.map(|(t, other)| (other, t))
+    }
+
+    fn query(&self, bit: u32, pos: Vec2, half_extents: Vec2) -> impl Iterator<Item = usize> + '_ {
+        // Entries are bucketed by center, widen the search by the largest collider on the layer
+        let reach = half_extents + self.layer_max_half_extents[bit as usize];
+        cells_overlapping(pos, reach)
+            .filter_map(move |cell| self.cells.get(&(bit, cell)))
+            .flatten()
+            .copied()
+    }
+}
+
+fn cells_overlapping(pos: Vec2, half_extents: Vec2) -> impl Iterator<Item = IVec2> {
+    let min = ((pos - half_extents) / COLLISION_GRID_CELL_SIZE)
+        .floor()
+        .as_ivec2();
+    let max = ((pos + half_extents) / COLLISION_GRID_CELL_SIZE)
+        .floor()
+        .as_ivec2();
+
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+}
+
+pub(crate) fn ordered(a: Entity, b: Entity) -> (Entity, Entity) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+pub fn rebuild_spatial_grid(
+    collider_query: Query<(Entity, &Transform, &Collider)>,
+    mut grid: ResMut<SpatialGrid>,
+) {
+    grid.clear();
+    for (entity, transform, collider) in collider_query.iter() {
+        grid.insert(entity, transform.translation.truncate(), *collider);
+    }
+}
+
+pub fn detect_collisions(
+    collider_query: Query<(Entity, &Transform, &Collider)>,
+    grid: Res<SpatialGrid>,
+    mut pairs: ResMut<CollisionPairs>,
+    mut found: Local<Parallel<Vec<(Entity, Entity)>>>,
+    mut started_events: EventWriter<CollisionStarted>,
+    mut ended_events: EventWriter<CollisionEnded>,
+) {
+    let grid = &*grid;
+    collider_query
+        .par_iter()
+        .for_each(|(entity, transform, collider)| {
+            let pos = transform.translation.truncate();
+            for layer in collider.layers.bits() {
+                for bit in collider.mask.bits() {
+                    if !grid.should_query(layer, bit) {
+                        continue;
+                    }
+
+                    for index in grid.query(bit, pos, collider.half_extents()) {
+                        let (other, other_pos, other_collider) = grid.entries[index];
+                        if entity != other
+                            && collider.interacts_with(&other_collider)
+                            && collider
+                                .shape
+                                .overlaps(pos, &other_collider.shape, other_pos)
+                        {
+                            found.borrow_local_mut().push(ordered(entity, other));
+                        }
+                    }
+                }
+            }
+        });
+
+    // Per-thread buffers fill in scheduling order, sort so events go out deterministically
+    let mut current = Vec::new();
+    found.drain_into(&mut current);
+    current.sort_unstable();
+    current.dedup();
+
+    let mut ended: Vec<_> = pairs
+        .0
+        .iter()
+        .filter(|pair| current.binary_search(pair).is_err())
+        .copied()
+        .collect();
+    ended.sort_unstable();
+    ended_events.send_batch(ended.into_iter().map(|(a, b)| CollisionEnded(a, b)));
+
+    started_events.send_batch(
+        current
+            .iter()
+            .filter(|pair| !pairs.0.contains(*pair))
+            .map(|(a, b)| CollisionStarted(*a, *b)),
+    );
+
+    pairs.0.clear();
+    pairs.0.extend(current);
+}
+
+type BulletSweep = (
+    Entity,
+    &'static Transform,
+    &'static PreviousPosition,
+    &'static BulletDirection,
+    &'static Shooter,
+    &'static Collider,
+);
+
+/// Bullets are swept from their previous position so fast shots and frame
+/// hitches can't tunnel through enemies. A bullet stops at the first thing on its path.
+/// The sweep runs on the grid, or as avian shape casts with the physics backend.
+fn handle_bullet_hits(
+    mut commands: Commands,
+    #[cfg(not(feature = "physics"))] sweeper: Res<SpatialGrid>,
+    #[cfg(feature = "physics")] sweeper: crate::physics::BulletSweep,
+    mut bullet_pool: ResMut<BulletPool>,
+    bullet_query: Query<BulletSweep, With<Bullet>>,
+    player_query: Query<&PowerUps, With<Player>>,
+    tuning: Res<Tuning>,
+    mut hit_events: EventWriter<BulletHit>,
+) {
+    let mut rng = rand::rng();
+    let damage_multiplier = player_query
+        .get_single()
+        .map_or(1.0, PowerUps::damage_multiplier);
+    for (entity, transform, previous, direction, shooter, collider) in bullet_query.iter() {
+        let end = transform.translation.truncate();
+        let Some((target, t)) = sweeper.sweep(entity, collider, previous.0, end) else {
+            continue;
+        };
+
+        let critical = rng.random::<f32>() < BULLET_CRIT_CHANCE;
+        let damage = if critical {
+            tuning.bullet_damage * BULLET_CRIT_MULTIPLIER
+        } else {
+            tuning.bullet_damage
+        } * damage_multiplier;
+        hit_events.send(BulletHit {
+            bullet: entity,
+            shooter: shooter.0,
+            target,
+            position: previous.0.lerp(end, t),
+            direction: direction.0.truncate().normalize_or_zero(),
+            damage,
+            knockback: tuning.bullet_knockback,
+            critical,
+        });
+        bullet_pool.release(&mut commands, entity);
     }
 }
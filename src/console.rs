@@ -0,0 +1,488 @@
+//! Drop-down developer console, opened with the backtick key.
+//!
+//! Plugins add commands with [`ConsoleAppExt::add_console_command`], which
+//! registers a one-shot system taking the arguments and returning the text to
+//! print. Tunable resource fields are exposed to `set` with
+//! [`ConsoleAppExt::add_console_var`].
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use bevy::{
+    ecs::system::SystemId,
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use crate::*;
+
+pub struct ConsolePlugin;
+
+/// What a command prints: `Ok` lines go to the log as they are, `Err` lines
+/// are prefixed with `error:`.
+pub type ConsoleResult = Result<String, String>;
+
+/// Arguments after the command name, split on whitespace.
+pub type ConsoleArgs = Vec<String>;
+
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub log: Vec<String>,
+    history: Vec<String>,
+    /// Position while browsing history with the arrow keys.
+    history_cursor: Option<usize>,
+    /// Submitted lines waiting for `run_console_commands`.
+    pending: Vec<String>,
+}
+
+/// Every registered command and variable, sorted by name for help and completion.
+#[derive(Resource, Default)]
+pub struct ConsoleRegistry {
+    commands: BTreeMap<&'static str, ConsoleCommand>,
+    vars: BTreeMap<&'static str, ConsoleVar>,
+}
+
+struct ConsoleCommand {
+    usage: &'static str,
+    /// Candidates offered when completing the command's arguments.
+    completions: Vec<String>,
+    system: SystemId<In<ConsoleArgs>, ConsoleResult>,
+}
+
+/// Reads the variable, or parses and writes it when given a value.
+type ConsoleVar = Box<dyn Fn(&mut World, Option<&str>) -> ConsoleResult + Send + Sync>;
+
+/// A resource that parses and validates its own named values, see
+/// [`ConsoleAppExt::add_console_vars`].
+pub trait ConsoleVars: Resource {
+    fn get_var(&self, name: &str) -> Option<String>;
+    fn set_var(&mut self, name: &str, value: &str) -> Result<(), String>;
+}
+
+pub trait ConsoleAppExt {
+    /// Registers `system` under `name`. `usage` is shown by `help`, and
+    /// `completions` are offered for its arguments on tab.
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        completions: &[&str],
+        system: impl IntoSystem<In<ConsoleArgs>, ConsoleResult, M> + 'static,
+    ) -> &mut Self;
+
+    /// Exposes a field of resource `R` to `set <name> [value]`.
+    fn add_console_var<R: Resource, T: FromStr + fmt::Display + 'static>(
+        &mut self,
+        name: &'static str,
+        field: fn(&mut R) -> &mut T,
+    ) -> &mut Self;
+
+    /// Exposes values of `R` to `set <name> [value]`, written through
+    /// [`ConsoleVars::set_var`] so they are validated like anywhere else.
+    fn add_console_vars<R: ConsoleVars>(&mut self, names: &[&'static str]) -> &mut Self;
+}
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleLogText;
+
+#[derive(Component)]
+struct ConsoleInputText;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleRegistry>()
+            .add_console_command("clear", "clear", &[], clear_command)
+            .add_console_command("timescale", "timescale <x>", &[], timescale_command)
+            .add_systems(OnEnter(GameState::GameInit), spawn_console)
+            .add_systems(
+                Update,
+                (
+                    handle_console_input,
+                    run_console_commands
+                        .run_if(|console: Res<Console>| !console.pending.is_empty()),
+                    update_console_text.run_if(resource_changed::<Console>),
+                )
+                    .chain()
+                    .before(GameSet::Input)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+impl ConsoleAppExt for App {
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        completions: &[&str],
+        system: impl IntoSystem<In<ConsoleArgs>, ConsoleResult, M> + 'static,
+    ) -> &mut Self {
+        let system = self.world_mut().register_system(system);
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleRegistry::default)
+            .commands
+            .insert(
+                name,
+                ConsoleCommand {
+                    usage,
+                    completions: completions.iter().map(|arg| arg.to_string()).collect(),
+                    system,
+                },
+            );
+        self
+    }
+
+    fn add_console_var<R: Resource, T: FromStr + fmt::Display + 'static>(
+        &mut self,
+        name: &'static str,
+        field: fn(&mut R) -> &mut T,
+    ) -> &mut Self {
+        let var = move |world: &mut World, value: Option<&str>| {
+            let value = value
+                .map(|value| {
+                    value
+                        .parse::<T>()
+                        .map_err(|_| format!("invalid value for {name}: {value}"))
+                })
+                .transpose()?;
+            let mut resource = world
+                .get_resource_mut::<R>()
+                .ok_or_else(|| format!("{name} is not available"))?;
+            if let Some(value) = value {
+                *field(&mut resource) = value;
+            }
+            Ok(format!(
+                "{name} = {}",
+                field(resource.bypass_change_detection())
+            ))
+        };
+        self.world_mut()
+            .get_resource_or_insert_with(ConsoleRegistry::default)
+            .vars
+            .insert(name, Box::new(var));
+        self
+    }
+
+    fn add_console_vars<R: ConsoleVars>(&mut self, names: &[&'static str]) -> &mut Self {
+        for &name in names {
+            let var = move |world: &mut World, value: Option<&str>| {
+                let mut resource = world
+                    .get_resource_mut::<R>()
+                    .ok_or_else(|| format!("{name} is not available"))?;
+                if let Some(value) = value {
+                    resource.set_var(name, value)?;
+                }
+                let value = resource
+                    .get_var(name)
+                    .ok_or_else(|| format!("unknown var: {name}"))?;
+                Ok(format!("{name} = {value}"))
+            };
+            self.world_mut()
+                .get_resource_or_insert_with(ConsoleRegistry::default)
+                .vars
+                .insert(name, Box::new(var));
+        }
+        self
+    }
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        let excess = self.log.len().saturating_sub(CONSOLE_MAX_LOG_LINES);
+        self.log.drain(..excess);
+    }
+
+    fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        self.history_cursor = None;
+        if line.trim().is_empty() {
+            return;
+        }
+
+        self.print(format!("> {line}"));
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            let excess = self.history.len().saturating_sub(CONSOLE_MAX_HISTORY);
+            self.history.drain(..excess);
+        }
+        self.pending.push(line);
+    }
+
+    /// Steps through history, `back` towards older lines.
+    fn browse_history(&mut self, back: bool) {
+        let cursor = match (self.history_cursor, back) {
+            (None, true) => self.history.len().checked_sub(1),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) => Some(i + 1).filter(|i| *i < self.history.len()),
+        };
+        self.history_cursor = cursor;
+        self.input = cursor.map(|i| self.history[i].clone()).unwrap_or_default();
+    }
+}
+
+impl ConsoleRegistry {
+    fn run(&self, world: &mut World, line: &str) -> ConsoleResult {
+        let mut words = line.split_whitespace().map(str::to_string);
+        let Some(name) = words.next() else {
+            return Ok(String::new());
+        };
+        let args: ConsoleArgs = words.collect();
+
+        match name.as_str() {
+            "help" => Ok(self.help()),
+            "set" => self.set(world, &args),
+            _ => {
+                let command = self
+                    .commands
+                    .get(name.as_str())
+                    .ok_or_else(|| format!("unknown command: {name}"))?;
+                world
+                    .run_system_with_input(command.system, args)
+                    .map_err(|err| err.to_string())?
+            }
+        }
+    }
+
+    fn help(&self) -> String {
+        let mut lines = vec!["help".to_string(), "set <var> [value]".to_string()];
+        lines.extend(
+            self.commands
+                .values()
+                .map(|command| command.usage.to_string()),
+        );
+        lines.join("\n")
+    }
+
+    fn set(&self, world: &mut World, args: &[String]) -> ConsoleResult {
+        let Some(name) = args.first() else {
+            // Without a name, list every variable with its value
+            let values: Result<Vec<_>, _> =
+                self.vars.values().map(|var| var(world, None)).collect();
+            return values.map(|values| values.join("\n"));
+        };
+
+        let var = self
+            .vars
+            .get(name.as_str())
+            .ok_or_else(|| format!("unknown var: {name}"))?;
+        var(world, args.get(1).map(String::as_str))
+    }
+
+    /// Candidates for the last word of `input`.
+    fn completions(&self, input: &str) -> Vec<String> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let completing_name = words.len() <= 1 && !input.ends_with(' ');
+        let partial = if input.ends_with(' ') {
+            ""
+        } else {
+            words.last().copied().unwrap_or_default()
+        };
+
+        let candidates: Vec<String> = if completing_name {
+            ["help", "set"]
+                .into_iter()
+                .chain(self.commands.keys().copied())
+                .map(str::to_string)
+                .collect()
+        } else if words[0] == "set" {
+            self.vars.keys().map(|name| name.to_string()).collect()
+        } else {
+            self.commands
+                .get(words[0])
+                .map(|command| command.completions.clone())
+                .unwrap_or_default()
+        };
+
+        let mut candidates: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(partial))
+            .collect();
+        candidates.sort();
+        candidates
+    }
+}
+
+fn spawn_console(mut commands: Commands, font: Res<GlobalFont>) {
+    let text_font = TextFont {
+        font: font.0.clone(),
+        font_size: CONSOLE_FONT_SIZE,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(CONSOLE_HEIGHT_PERCENT),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::End,
+                padding: UiRect::all(Val::Px(8.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+            GlobalZIndex(i32::MAX),
+            ConsolePanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::default(), text_font.clone(), ConsoleLogText));
+            parent.spawn((
+                Text::default(),
+                text_font,
+                TextColor(Color::srgb(1.0, 0.85, 0.3)),
+                ConsoleInputText,
+            ));
+        });
+}
+
+fn handle_console_input(
+    mut console: ResMut<Console>,
+    registry: Res<ConsoleRegistry>,
+    mut key_events: EventReader<KeyboardInput>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut mouse_button_input: ResMut<ButtonInput<MouseButton>>,
+) {
+    for event in key_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Enter => console.submit(),
+            Key::Escape => console.open = false,
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::ArrowUp => console.browse_history(true),
+            Key::ArrowDown => console.browse_history(false),
+            Key::Tab => complete_input(&mut console, &registry),
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => {}
+        }
+    }
+
+    // Typing must not move the player or fire the gun
+    if console.open {
+        keyboard_input.reset_all();
+        mouse_button_input.reset_all();
+    }
+}
+
+/// Completes the last word as far as all candidates agree, listing them when
+/// there is more than one.
+fn complete_input(console: &mut Console, registry: &ConsoleRegistry) {
+    let candidates = registry.completions(&console.input);
+    let Some(first) = candidates.first() else {
+        return;
+    };
+
+    let common = candidates.iter().fold(first.as_str(), |common, candidate| {
+        let len = common
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        &common[..len]
+    });
+    let start = console
+        .input
+        .rfind(' ')
+        .map(|space| space + 1)
+        .unwrap_or_default();
+    let mut input = format!("{}{common}", &console.input[..start]);
+    if candidates.len() == 1 {
+        input.push(' ');
+    } else {
+        console.print(candidates.join("  "));
+    }
+    console.input = input;
+}
+
+fn run_console_commands(world: &mut World) {
+    let lines = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in lines {
+        let output = world
+            .resource_scope(|world, registry: Mut<ConsoleRegistry>| registry.run(world, &line));
+        let mut console = world.resource_mut::<Console>();
+        match output {
+            Ok(text) if text.is_empty() => {}
+            Ok(text) => console.print(text),
+            Err(err) => console.print(format!("error: {err}")),
+        }
+    }
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut panel_query: Query<&mut Node, With<ConsolePanel>>,
+    mut log_query: Query<&mut Text, (With<ConsoleLogText>, Without<ConsoleInputText>)>,
+    mut input_query: Query<&mut Text, (With<ConsoleInputText>, Without<ConsoleLogText>)>,
+) {
+    if let Ok(mut node) = panel_query.get_single_mut() {
+        node.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    if !console.open {
+        return;
+    }
+
+    if let Ok(mut text) = log_query.get_single_mut() {
+        let lines: Vec<&str> = console.log.iter().flat_map(|entry| entry.lines()).collect();
+        let start = lines.len().saturating_sub(CONSOLE_VISIBLE_LINES);
+        text.0 = lines[start..].join("\n");
+    }
+    if let Ok(mut text) = input_query.get_single_mut() {
+        text.0 = format!("> {}_", console.input);
+    }
+}
+
+fn clear_command(In(_): In<ConsoleArgs>, mut console: ResMut<Console>) -> ConsoleResult {
+    console.log.clear();
+    Ok(String::new())
+}
+
+fn timescale_command(In(args): In<ConsoleArgs>, mut time: ResMut<Time<Virtual>>) -> ConsoleResult {
+    if let Some(scale) = args.first() {
+        let scale: f32 = scale
+            .parse()
+            .map_err(|_| format!("invalid time scale: {scale}"))?;
+        if !(0.0..=CONSOLE_MAX_TIME_SCALE).contains(&scale) {
+            return Err(format!(
+                "time scale must be between 0 and {CONSOLE_MAX_TIME_SCALE}"
+            ));
+        }
+        time.set_relative_speed(scale);
+    }
+    Ok(format!("timescale = {}", time.relative_speed()))
+}
+
+/// Parses the `index`th argument, falling back to `default` when it is missing.
+pub fn console_arg<T: FromStr>(args: &[String], index: usize, default: T) -> Result<T, String> {
+    match args.get(index) {
+        Some(arg) => arg.parse().map_err(|_| format!("invalid argument: {arg}")),
+        None => Ok(default),
+    }
+}
@@ -5,6 +5,7 @@ pub const BG_COLOR: (u8, u8, u8) = (197, 204, 184);
 
 //sprites
 pub const SPRITE_SHEET_PATH: &str = "assets.png";
+pub const FONT_PATH: &str = "monogram.ttf";
 pub const SPRITE_SCALE_FACTOR: f32 = 3.0;
 pub const TILE_W: u32 = 16;
 pub const TILE_H: u32 = 16;
@@ -16,19 +17,221 @@ pub const NUM_WORLD_DECORATIONS: u32 = 1000;
 pub const WORLD_W: f32 = 3000.0;
 pub const WORLD_H: f32 = 2500.0;
 
+//biome
+pub const BIOME_NOISE_SCALE: f32 = 1.0 / 1400.0;
+pub const BIOME_TILE_SIZE: f32 = 96.0;
+pub const BIOME_BG_LERP_SPEED: f32 = 2.0;
+pub const SWAMP_SPEED_MULTIPLIER: f32 = 0.6;
+
 //player
-pub const PLAYER_SPEED: f32 = 4.0;
+/// World units per second.
+pub const PLAYER_SPEED: f32 = 240.0;
+pub const PLAYER_COLLIDER_RADIUS: f32 = 5.0;
+pub const PLAYER_HEALTH: f32 = 100.0;
+pub const PLAYER_INVULNERABILITY_SECS: f32 = 0.5;
 
 //enemy
 pub const MAX_NUM_ENEMIES: u32 = 50000;
 pub const SPAWN_RATE_PER_SECOND: u32 = 100;
 pub const ENEMY_SPAWN_INTERVAL: f32 = 1.0;
-pub const ENEMY_SPEED: f32 = 2.0;
+/// World units per second.
+pub const ENEMY_SPEED: f32 = 120.0;
 pub const ENEMY_HEALTH: f32 = 100.0;
+pub const ENEMY_COLLIDER_RADIUS: f32 = 7.0;
+pub const ENEMY_CONTACT_DAMAGE: f32 = 10.0;
+pub const ENEMY_SPAWN_MIN_DISTANCE: f32 = 100.0;
+pub const ENEMY_SPAWN_MAX_DISTANCE: f32 = 2000.0;
+/// Knockback velocity decays as `exp(-damping * seconds)`.
+pub const ENEMY_KNOCKBACK_DAMPING: f32 = 13.0;
+pub const ENEMY_HIT_FLASH_SECS: f32 = 0.08;
+/// Sprite tint while flashing; channels above 1.0 wash the texture out to white.
+pub const ENEMY_HIT_FLASH_INTENSITY: f32 = 6.0;
+pub const ENEMY_DEATH_CLIP_SECS: f32 = 0.4;
+
+//death
+pub const MAX_CORPSES: usize = 2000;
+pub const CORPSE_LIFE_TIME_IN_SECS: f32 = 6.0;
+pub const CORPSE_BRIGHTNESS: f32 = 0.45;
+pub const CORPSE_ALPHA: f32 = 0.8;
+
+//camera
+pub const CAMERA_DEAD_ZONE_W: f32 = 40.0;
+pub const CAMERA_DEAD_ZONE_H: f32 = 30.0;
+/// Fraction of the way from the player to the cursor the camera leads by.
+pub const CAMERA_LOOK_AHEAD: f32 = 0.25;
+pub const CAMERA_MAX_LOOK_AHEAD: f32 = 160.0;
+/// Seconds for the camera to close ~63% of the gap to its target.
+pub const CAMERA_FOLLOW_TIME_CONSTANT: f32 = 0.15;
+pub const CAMERA_MIN_ZOOM: f32 = 0.5;
+pub const CAMERA_MAX_ZOOM: f32 = 3.0;
+pub const FREE_CAMERA_MIN_ZOOM: f32 = 0.1;
+pub const FREE_CAMERA_MAX_ZOOM: f32 = 20.0;
+pub const CINEMATIC_CAMERA_MIN_ZOOM: f32 = 0.25;
+pub const CINEMATIC_CAMERA_MAX_ZOOM: f32 = 5.0;
+/// Every this many waves, the wave opens with a camera pull-back.
+pub const CINEMATIC_WAVE_INTERVAL: u32 = 5;
+pub const CINEMATIC_WAVE_INTRO_ZOOM: f32 = 4.0;
+/// Trauma lost per second.
+pub const CAMERA_SHAKE_DECAY: f32 = 1.5;
+/// Offset in screen pixels at full trauma.
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 24.0;
+pub const CAMERA_SHAKE_FREQUENCY: f32 = 18.0;
+pub const GUN_FIRE_TRAUMA: f32 = 0.08;
+pub const PLAYER_HIT_TRAUMA: f32 = 0.5;
+
+//progression
+pub const WAVE_DURATION_SECS: f32 = 60.0;
+/// Extra enemies per spawn tick for each wave after the first.
+pub const WAVE_SPAWN_RATE_STEP: u32 = 25;
+pub const XP_FIRST_LEVEL: u32 = 10;
+pub const XP_PER_LEVEL: u32 = 5;
+pub const SCORE_PER_XP: u32 = 10;
+
+//lod
+pub const LOD_UPDATE_INTERVAL: f32 = 0.1;
+pub const LOD_VIEW_MARGIN: f32 = 64.0;
+pub const LOD_NEAR_DISTANCE: f32 = 1200.0;
+pub const LOD_FAR_UPDATE_INTERVAL: u32 = 4;
+pub const ENEMY_LEASH_DISTANCE: f32 = 2600.0;
+pub const ENEMY_RECYCLE_MIN_DISTANCE: f32 = 1000.0;
+pub const ENEMY_RECYCLE_MAX_DISTANCE: f32 = 1800.0;
 
 //bullet
 pub const BULLET_SPAWN_INTERVAL: f32 = 0.1;
-pub const BULLET_SPEED: f32 = 15.0;
-pub const BULLET_DAMAGE: f32 = 100.0;
+/// World units per second.
+pub const BULLET_SPEED: f32 = 900.0;
+pub const BULLET_DAMAGE: f32 = 35.0;
+/// World units per second given to a unit-mass target.
+pub const BULLET_KNOCKBACK: f32 = 480.0;
+pub const BULLET_CRIT_CHANCE: f32 = 0.1;
+pub const BULLET_CRIT_MULTIPLIER: f32 = 2.0;
 pub const BULLET_LIFE_TIME_IN_SECS: f32 = 0.8;
 pub const NUM_OF_BULLET_PER_SHOT: u32 = 3;
+pub const BULLET_COLLIDER_RADIUS: f32 = 3.5;
+/// Distance from the gun's centre to where the muzzle flash starts.
+pub const GUN_MUZZLE_OFFSET: f32 = 24.0;
+pub const GUN_MAGAZINE_SIZE: u32 = 30;
+pub const GUN_START_RESERVE: u32 = 150;
+pub const GUN_MAX_RESERVE: u32 = 300;
+pub const GUN_RELOAD_SECS: f32 = 1.5;
+pub const RELOAD_BAR_WIDTH: f32 = 48.0;
+pub const RELOAD_BAR_HEIGHT: f32 = 6.0;
+/// Height of the reload bar above the gun.
+pub const RELOAD_BAR_OFFSET: f32 = 36.0;
+
+//damage numbers
+pub const DAMAGE_NUMBER_LIFE_TIME_IN_SECS: f32 = 0.8;
+/// Hits on an enemy within this long of its last number are added to it.
+pub const DAMAGE_NUMBER_MERGE_SECS: f32 = 0.25;
+/// Screen pixels per second at any zoom.
+pub const DAMAGE_NUMBER_RISE_SPEED: f32 = 60.0;
+pub const DAMAGE_NUMBER_FONT_SIZE: f32 = 32.0;
+pub const DAMAGE_NUMBER_CRIT_SCALE: f32 = 1.4;
+
+//hud
+pub const HUD_MARGIN: f32 = 12.0;
+pub const HUD_BAR_WIDTH: f32 = 240.0;
+pub const GUN_NAME: &str = "Shotgun";
+
+//debug overlay
+pub const DEBUG_PANEL_UPDATE_INTERVAL: f32 = 0.25;
+
+//audio
+pub const AUDIO_SAMPLE_RATE: u32 = 22050;
+/// Effects playing at once across all kinds.
+pub const AUDIO_MAX_VOICES: usize = 16;
+/// Distance from the player at which positional effects fade out completely.
+pub const AUDIO_FALLOFF_DISTANCE: f32 = 1200.0;
+/// Random playback speed change per effect, up or down.
+pub const AUDIO_PITCH_VARIATION: f32 = 0.08;
+pub const AUDIO_GROAN_INTERVAL: f32 = 2.0;
+
+//settings
+pub const SETTINGS_PATH: &str = "settings.cfg";
+/// Window sizes offered on the settings screen.
+pub const RESOLUTIONS: [(u32, u32); 4] = [(1200, 900), (1280, 720), (1600, 900), (1920, 1080)];
+/// Frame rate caps offered on the settings screen, 0 for none.
+pub const FPS_CAPS: [u32; 5] = [0, 30, 60, 120, 144];
+
+//menu
+pub const MENU_FONT_SIZE: f32 = 30.0;
+pub const MENU_TITLE_FONT_SIZE: f32 = 64.0;
+pub const MENU_BUTTON_WIDTH: f32 = 240.0;
+pub const MENU_LABEL_WIDTH: f32 = 220.0;
+pub const MENU_VALUE_WIDTH: f32 = 160.0;
+
+//console
+pub const CONSOLE_HEIGHT_PERCENT: f32 = 40.0;
+pub const CONSOLE_FONT_SIZE: f32 = 24.0;
+pub const CONSOLE_VISIBLE_LINES: usize = 14;
+pub const CONSOLE_MAX_LOG_LINES: usize = 200;
+pub const CONSOLE_MAX_HISTORY: usize = 50;
+pub const CONSOLE_MAX_TIME_SCALE: f32 = 10.0;
+
+//minimap
+pub const MINIMAP_SIZE: f32 = 200.0;
+pub const MINIMAP_MARGIN: f32 = 12.0;
+/// Texels per side; enemies are binned at this resolution.
+pub const MINIMAP_RESOLUTION: u32 = 64;
+pub const MINIMAP_DEFAULT_RANGE: f32 = 2400.0;
+pub const MINIMAP_MIN_RANGE: f32 = 600.0;
+pub const MINIMAP_MAX_RANGE: f32 = 9600.0;
+pub const MINIMAP_UPDATE_INTERVAL: f32 = 0.1;
+/// Enemies in one texel for it to show at full heat.
+pub const MINIMAP_HEAT_SATURATION: f32 = 20.0;
+
+//particles
+pub const MAX_PARTICLES: usize = 4000;
+pub const PARTICLE_SIZE: f32 = 3.0;
+
+//collision
+pub const COLLISION_GRID_CELL_SIZE: f32 = 64.0;
+pub const PHYSICS_LINEAR_DAMPING: f32 = 8.0;
+
+//pool
+pub const POOL_METRICS_LOG_INTERVAL: f32 = 10.0;
+
+//dash
+/// World units covered by one dash.
+pub const DASH_DISTANCE: f32 = 220.0;
+pub const DASH_DURATION_SECS: f32 = 0.15;
+/// Outlasts the dash a little so it doesn't end inside the horde.
+pub const DASH_INVULNERABILITY_SECS: f32 = 0.3;
+/// Time to refill one spent charge.
+pub const DASH_COOLDOWN_SECS: f32 = 2.0;
+pub const DASH_LEVELS_PER_CHARGE: u32 = 5;
+pub const DASH_MAX_CHARGES: u32 = 3;
+pub const DASH_AFTERIMAGE_INTERVAL: f32 = 0.03;
+pub const DASH_AFTERIMAGE_LIFE_TIME_IN_SECS: f32 = 0.25;
+
+//pickups
+/// Chance for each kill to drop a pickup besides its XP gem.
+pub const PICKUP_DROP_CHANCE: f32 = 0.02;
+pub const PICKUP_WORLD_SPAWN_INTERVAL: f32 = 20.0;
+pub const PICKUP_WORLD_SPAWN_MIN_DISTANCE: f32 = 300.0;
+pub const PICKUP_WORLD_SPAWN_MAX_DISTANCE: f32 = 900.0;
+pub const PICKUP_LIFE_TIME_IN_SECS: f32 = 20.0;
+/// Pickups blink for this long before they expire.
+pub const PICKUP_BLINK_SECS: f32 = 4.0;
+/// Blinks per second.
+pub const PICKUP_BLINK_RATE: f32 = 6.0;
+pub const PICKUP_SIZE: f32 = 12.0;
+pub const PICKUP_COLLIDER_RADIUS: f32 = 5.0;
+pub const HEALTH_PACK_AMOUNT: f32 = 30.0;
+pub const AMMO_PACK_ROUNDS: u32 = 60;
+pub const BOMB_RADIUS: f32 = 900.0;
+pub const BOMB_TRAUMA: f32 = 0.8;
+pub const POWER_UP_DURATION_SECS: f32 = 10.0;
+/// Stacked pickups of one power-up never leave more than this on the clock.
+pub const POWER_UP_MAX_SECS: f32 = 30.0;
+pub const POWER_UP_DAMAGE_MULTIPLIER: f32 = 2.0;
+pub const POWER_UP_FIRE_INTERVAL_MULTIPLIER: f32 = 0.5;
+pub const POWER_UP_SPEED_MULTIPLIER: f32 = 1.5;
+/// Past this many gems on the ground, kills grant their XP directly.
+pub const MAX_XP_GEMS: usize = 3000;
+pub const XP_GEM_SIZE: f32 = 6.0;
+/// Gems closer than this start flying to the player.
+pub const XP_GEM_ATTRACT_RADIUS: f32 = 90.0;
+pub const XP_GEM_COLLECT_RADIUS: f32 = 16.0;
+pub const XP_GEM_MAGNET_ACCELERATION: f32 = 1500.0;
+pub const XP_GEM_MAGNET_MAX_SPEED: f32 = 1500.0;
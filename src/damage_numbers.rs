@@ -0,0 +1,140 @@
+use bevy::{
+    color::palettes::css::{ORANGE_RED, WHITE},
+    ecs::entity::EntityHashMap,
+    prelude::*,
+};
+
+use crate::{events::EnemyDamaged, settings::Settings, *};
+
+pub struct DamageNumbersPlugin;
+
+/// Floating text showing the damage dealt to `enemy`. Further hits on the
+/// same enemy shortly after are added to it instead of spawning a new one.
+#[derive(Component)]
+pub struct DamageNumber {
+    pub enemy: Entity,
+    pub total: f32,
+    pub critical: bool,
+    pub origin: Vec2,
+    pub age: f32,
+}
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_damage_numbers, update_damage_numbers)
+                .chain()
+                .in_set(GameSet::Ui)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+impl DamageNumber {
+    fn text(&self) -> String {
+        format!("{:.0}", self.total)
+    }
+
+    fn color(&self) -> Color {
+        if self.critical {
+            ORANGE_RED.into()
+        } else {
+            WHITE.into()
+        }
+    }
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    font: Res<GlobalFont>,
+    mut damaged_events: EventReader<EnemyDamaged>,
+    mut number_query: Query<(&mut DamageNumber, &mut Text2d, &mut TextColor)>,
+) {
+    if !settings.damage_numbers {
+        damaged_events.clear();
+        return;
+    }
+
+    // Sum this frame's hits per enemy first so a shotgun blast is one number
+    let mut hits: EntityHashMap<(f32, bool, Vec2)> = EntityHashMap::default();
+    for event in damaged_events.read() {
+        let hit = hits
+            .entry(event.enemy)
+            .or_insert((0.0, false, event.position));
+        hit.0 += event.amount;
+        hit.1 |= event.critical;
+    }
+    if hits.is_empty() {
+        return;
+    }
+
+    for (mut number, mut text, mut color) in number_query.iter_mut() {
+        if number.age > DAMAGE_NUMBER_MERGE_SECS {
+            continue;
+        }
+        let Some((amount, critical, _)) = hits.remove(&number.enemy) else {
+            continue;
+        };
+
+        number.total += amount;
+        number.critical |= critical;
+        text.0 = number.text();
+        color.0 = number.color();
+    }
+
+    for (enemy, (total, critical, position)) in hits {
+        let number = DamageNumber {
+            enemy,
+            total,
+            critical,
+            origin: position,
+            age: 0.0,
+        };
+        commands.spawn((
+            Text2d::new(number.text()),
+            TextFont {
+                font: font.0.clone(),
+                font_size: DAMAGE_NUMBER_FONT_SIZE,
+                ..default()
+            },
+            TextColor(number.color()),
+            Transform::from_translation(position.extend(20.0)),
+            number,
+        ));
+    }
+}
+
+fn update_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera_query: Query<&OrthographicProjection, With<Camera>>,
+    mut number_query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut TextColor)>,
+) {
+    // Counter the camera zoom so numbers keep the same size on screen
+    let zoom = camera_query
+        .get_single()
+        .map_or(1.0, |projection| projection.scale);
+
+    for (entity, mut number, mut transform, mut color) in number_query.iter_mut() {
+        number.age += time.delta_secs();
+        if number.age >= DAMAGE_NUMBER_LIFE_TIME_IN_SECS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let rise = DAMAGE_NUMBER_RISE_SPEED * number.age * zoom;
+        transform.translation = (number.origin + Vec2::Y * rise).extend(20.0);
+        let scale = if number.critical {
+            DAMAGE_NUMBER_CRIT_SCALE
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::splat(scale * zoom);
+
+        // Fully opaque for the first half, then fade out
+        let life = number.age / DAMAGE_NUMBER_LIFE_TIME_IN_SECS;
+        color.0.set_alpha((2.0 - life * 2.0).min(1.0));
+    }
+}
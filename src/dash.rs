@@ -0,0 +1,215 @@
+//! A short burst of speed on the dash key, through enemies and unhurt, leaving
+//! fading afterimages behind.
+
+use bevy::prelude::*;
+
+use crate::{
+    collision::{Collider, CollisionLayers},
+    events::PlayerLeveledUp,
+    player::{movement_input, Invulnerable, Player, PlayerMovement},
+    resources::CursorPosition,
+    settings::Settings,
+    *,
+};
+
+pub struct DashPlugin;
+
+const DASH_AFTERIMAGE_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.6);
+
+/// Dash charges on the player. Spent charges come back one per cooldown.
+#[derive(Component)]
+pub struct Dash {
+    pub charges: u32,
+    pub max_charges: u32,
+    pub cooldown: Timer,
+}
+
+/// The player is mid-dash, moving at `velocity` world units per second.
+#[derive(Component)]
+pub struct Dashing {
+    pub velocity: Vec2,
+    pub timer: Timer,
+    afterimage: Timer,
+}
+
+/// Query filter for the player while not mid-dash.
+pub type WalkingPlayer = (With<Player>, Without<Dashing>);
+
+/// A fading copy of the player's sprite left along the dash.
+#[derive(Component)]
+struct Afterimage(Timer);
+
+impl Plugin for DashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                start_dash.in_set(GameSet::Input),
+                move_dashing
+                    .in_set(GameSet::Movement)
+                    .in_set(PlayerMovement),
+                (recharge_dash, grant_dash_charges).in_set(GameSet::Cleanup),
+                fade_afterimages.in_set(GameSet::Animation),
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Self {
+            charges: 1,
+            max_charges: 1,
+            cooldown: Timer::from_seconds(DASH_COOLDOWN_SECS, TimerMode::Once),
+        }
+    }
+}
+
+impl Dash {
+    /// Charges unlocked by `level`: one to start, another every
+    /// [`DASH_LEVELS_PER_CHARGE`] levels up to [`DASH_MAX_CHARGES`].
+    pub fn max_charges_at(level: u32) -> u32 {
+        (1 + level / DASH_LEVELS_PER_CHARGE).min(DASH_MAX_CHARGES)
+    }
+}
+
+/// Dashes along the movement keys, or towards the cursor when standing still.
+fn start_dash(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    cursor_pos: Res<CursorPosition>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Dash,
+            &mut Collider,
+            Option<&Invulnerable>,
+        ),
+        WalkingPlayer,
+    >,
+) {
+    if !keyboard_input.just_pressed(settings.keys.dash) {
+        return;
+    }
+    let Ok((entity, transform, mut dash, mut collider, invulnerable)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+    if dash.charges == 0 {
+        return;
+    }
+
+    let player_pos = transform.translation.truncate();
+    let mut direction = movement_input(&keyboard_input, &settings.keys);
+    if direction == Vec2::ZERO {
+        direction = cursor_pos
+            .0
+            .map_or(Vec2::ZERO, |pos| (pos - player_pos).normalize_or_zero());
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    dash.charges -= 1;
+    collider.mask = collider.mask.without(CollisionLayers::ENEMY);
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.insert(Dashing {
+        velocity: direction * DASH_DISTANCE / DASH_DURATION_SECS,
+        timer: Timer::from_seconds(DASH_DURATION_SECS, TimerMode::Once),
+        afterimage: Timer::from_seconds(DASH_AFTERIMAGE_INTERVAL, TimerMode::Repeating),
+    });
+    // Don't cut short a longer invulnerability from a recent hit
+    if invulnerable.is_none_or(|timer| timer.remaining_secs() < DASH_INVULNERABILITY_SECS) {
+        entity_commands.insert(Invulnerable(Timer::from_seconds(
+            DASH_INVULNERABILITY_SECS,
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn move_dashing(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &mut Transform, &mut Dashing, &mut Collider, &Sprite)>,
+) {
+    let Ok((entity, mut transform, mut dashing, mut collider, sprite)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    transform.translation += (dashing.velocity * time.delta_secs()).extend(0.0);
+
+    if dashing.afterimage.tick(time.delta()).just_finished() {
+        commands.spawn((
+            Sprite {
+                color: DASH_AFTERIMAGE_COLOR,
+                ..sprite.clone()
+            },
+            Transform {
+                translation: transform.translation.with_z(transform.translation.z - 1.0),
+                ..*transform
+            },
+            Afterimage(Timer::from_seconds(
+                DASH_AFTERIMAGE_LIFE_TIME_IN_SECS,
+                TimerMode::Once,
+            )),
+        ));
+    }
+
+    if dashing.timer.tick(time.delta()).finished() {
+        collider.mask = collider.mask | CollisionLayers::ENEMY;
+        commands.entity(entity).remove::<Dashing>();
+    }
+}
+
+fn recharge_dash(time: Res<Time>, mut dash_query: Query<&mut Dash>) {
+    for mut dash in dash_query.iter_mut() {
+        if dash.charges >= dash.max_charges {
+            continue;
+        }
+        if dash.cooldown.tick(time.delta()).finished() {
+            dash.charges += 1;
+            dash.cooldown.reset();
+        }
+    }
+}
+
+/// Levelling up unlocks extra charges, which come ready to use.
+fn grant_dash_charges(
+    mut level_events: EventReader<PlayerLeveledUp>,
+    mut dash_query: Query<&mut Dash, With<Player>>,
+) {
+    let Some(level) = level_events.read().map(|event| event.level).max() else {
+        return;
+    };
+    let Ok(mut dash) = dash_query.get_single_mut() else {
+        return;
+    };
+
+    let max_charges = Dash::max_charges_at(level);
+    if max_charges > dash.max_charges {
+        dash.charges += max_charges - dash.max_charges;
+        dash.max_charges = max_charges;
+    }
+}
+
+fn fade_afterimages(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut afterimage_query: Query<(Entity, &mut Afterimage, &mut Sprite)>,
+) {
+    for (entity, mut afterimage, mut sprite) in afterimage_query.iter_mut() {
+        if afterimage.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite
+            .color
+            .set_alpha(DASH_AFTERIMAGE_COLOR.alpha() * afterimage.0.fraction_remaining());
+    }
+}
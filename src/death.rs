@@ -0,0 +1,138 @@
+use std::{collections::VecDeque, f32::consts::FRAC_PI_2};
+
+use bevy::prelude::*;
+
+use crate::{enemy::Enemy, pool::EnemyPool, *};
+
+pub struct DeathPlugin;
+
+/// An enemy playing its death clip. It has no collider and no AI, and goes
+/// back to the pool once the clip ends.
+#[derive(Component, Deref, DerefMut)]
+pub struct Dying(pub Timer);
+
+/// Fading body left behind by a dead enemy.
+#[derive(Component, Deref, DerefMut)]
+pub struct Corpse(pub Timer);
+
+/// Live corpses, oldest first, so the oldest can be dropped once there are
+/// more than [`MAX_CORPSES`].
+#[derive(Resource, Default)]
+pub struct Corpses(VecDeque<Entity>);
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Corpses>().add_systems(
+            Update,
+            (
+                finish_dying_enemies.in_set(GameSet::Cleanup),
+                (animate_dying_enemies, fade_corpses).in_set(GameSet::Animation),
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+impl Dying {
+    pub fn new() -> Self {
+        Self(Timer::from_seconds(ENEMY_DEATH_CLIP_SECS, TimerMode::Once))
+    }
+}
+
+impl Default for Dying {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn corpse_color() -> Color {
+    Color::srgba(
+        CORPSE_BRIGHTNESS,
+        CORPSE_BRIGHTNESS,
+        CORPSE_BRIGHTNESS,
+        CORPSE_ALPHA,
+    )
+}
+
+fn animate_dying_enemies(mut enemy_query: Query<(&mut Sprite, &mut Transform, &Enemy, &Dying)>) {
+    for (mut sprite, mut transform, enemy, dying) in enemy_query.iter_mut() {
+        let progress = dying.fraction();
+        let frames = enemy.kind.death_frames();
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            let frame = ((progress * frames.len() as f32) as usize).min(frames.len() - 1);
+            atlas.index = frames[frame];
+        }
+
+        // Topple over backwards, away from the way the enemy faces
+        let fall = if sprite.flip_x { -FRAC_PI_2 } else { FRAC_PI_2 };
+        transform.rotation = Quat::from_rotation_z(fall * progress);
+        sprite.color = Color::WHITE.mix(&corpse_color(), progress);
+    }
+}
+
+fn finish_dying_enemies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut enemy_pool: ResMut<EnemyPool>,
+    mut corpses: ResMut<Corpses>,
+    mut enemy_query: Query<(Entity, &Sprite, &Transform, &mut Dying)>,
+) {
+    for (entity, sprite, transform, mut dying) in enemy_query.iter_mut() {
+        if !dying.tick(time.delta()).finished() {
+            continue;
+        }
+
+        let mut corpse_transform = *transform;
+        corpse_transform.translation.z = 0.5;
+        corpses.0.push_back(
+            commands
+                .spawn((
+                    Sprite {
+                        color: corpse_color(),
+                        ..sprite.clone()
+                    },
+                    corpse_transform,
+                    Corpse(Timer::from_seconds(
+                        CORPSE_LIFE_TIME_IN_SECS,
+                        TimerMode::Once,
+                    )),
+                ))
+                .id(),
+        );
+        enemy_pool.release(&mut commands, entity);
+    }
+
+    while corpses.0.len() > MAX_CORPSES {
+        if let Some(oldest) = corpses.0.pop_front() {
+            commands.entity(oldest).despawn();
+        }
+    }
+}
+
+fn fade_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut corpses: ResMut<Corpses>,
+    mut corpse_query: Query<(&mut Sprite, &mut Corpse)>,
+) {
+    for (mut sprite, mut corpse) in corpse_query.iter_mut() {
+        corpse.tick(time.delta());
+        sprite
+            .color
+            .set_alpha(CORPSE_ALPHA * corpse.fraction_remaining());
+    }
+
+    // Corpses expire in spawn order, so the finished ones are all at the front.
+    // They leave the queue as they are despawned, or `finish_dying_enemies`
+    // could pick one as the oldest and despawn it again.
+    while let Some(&oldest) = corpses.0.front() {
+        if !corpse_query
+            .get(oldest)
+            .is_ok_and(|(_, corpse)| corpse.finished())
+        {
+            break;
+        }
+        corpses.0.pop_front();
+        commands.entity(oldest).despawn();
+    }
+}
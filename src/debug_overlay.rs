@@ -0,0 +1,427 @@
+//! Developer overlay, compiled into debug builds with the `debug_overlay`
+//! cargo feature. Release builds leave it out whatever the features.
+//!
+//! F3 shows or hides the whole overlay, which starts hidden. While it is shown, F4 toggles
+//! collider outlines, F5 bullet directions, F6 the collision grid, F7 the
+//! enemy spawn and leash rings and F8 the text panels.
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    color::palettes::css::{GOLD, LIME, ORANGE, RED, SKY_BLUE, YELLOW},
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::entity::Entities,
+    prelude::*,
+    time::common_conditions::on_timer,
+};
+
+use crate::{
+    camera::CameraMode,
+    collision::{Collider, ColliderShape, SpatialGrid},
+    damage_numbers::DamageNumber,
+    death::{Corpse, Dying},
+    enemy::Enemy,
+    gun::{Bullet, BulletDirection},
+    particles::Particle,
+    player::Player,
+    pool::Pooled,
+    *,
+};
+
+pub struct DebugOverlayPlugin;
+
+/// Which overlay layers are drawn.
+#[derive(Resource)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    pub colliders: bool,
+    pub bullets: bool,
+    pub grid: bool,
+    pub spawn_rings: bool,
+    pub panels: bool,
+}
+
+/// Wall time spent in each [`GameSet`] last frame, measured by probe systems
+/// between the sets, in milliseconds and smoothed.
+///
+/// This is per set, not per system, and approximate since systems outside the
+/// sets can run between the probes. Bevy only reports single systems through
+/// tracing spans. For those, build with `--features bevy/trace_tracy` and
+/// attach Tracy.
+#[derive(Resource, Default)]
+pub struct SetTimings {
+    started: Option<Instant>,
+    pub millis: [f32; GAME_SETS.len()],
+}
+
+const GAME_SETS: [(&str, GameSet); 8] = [
+    ("input", GameSet::Input),
+    ("movement", GameSet::Movement),
+    ("collision", GameSet::Collision),
+    ("damage", GameSet::Damage),
+    ("cleanup", GameSet::Cleanup),
+    ("animation", GameSet::Animation),
+    ("camera", GameSet::Camera),
+    ("ui", GameSet::Ui),
+];
+
+#[derive(Component)]
+struct FpsText;
+
+#[derive(Component)]
+struct CountsText;
+
+#[derive(Component)]
+struct TimingsText;
+
+#[derive(Component)]
+struct StateText;
+
+#[derive(Component)]
+struct OverlayPanels;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .init_resource::<SetTimings>()
+            .add_systems(OnEnter(GameState::GameInit), spawn_overlay_panels)
+            .add_systems(Update, start_set_timings.before(GameSet::Input))
+            .add_systems(
+                Update,
+                (
+                    toggle_overlay_layers.in_set(GameSet::Input),
+                    (
+                        show_overlay_panels.run_if(resource_changed::<DebugOverlay>),
+                        (
+                            update_fps_text,
+                            update_counts_text,
+                            update_timings_text,
+                            update_state_text,
+                        )
+                            .run_if(|overlay: Res<DebugOverlay>| overlay.visible && overlay.panels)
+                            .run_if(on_timer(Duration::from_secs_f32(
+                                DEBUG_PANEL_UPDATE_INTERVAL,
+                            ))),
+                        (
+                            draw_colliders.run_if(|overlay: Res<DebugOverlay>| overlay.colliders),
+                            draw_bullet_directions
+                                .run_if(|overlay: Res<DebugOverlay>| overlay.bullets),
+                            draw_spatial_grid.run_if(|overlay: Res<DebugOverlay>| overlay.grid),
+                            draw_spawn_rings
+                                .run_if(|overlay: Res<DebugOverlay>| overlay.spawn_rings),
+                        )
+                            .run_if(|overlay: Res<DebugOverlay>| overlay.visible),
+                    )
+                        .in_set(GameSet::Ui),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+
+        for (i, (_, set)) in GAME_SETS.iter().enumerate() {
+            let probe = (move |mut timings: ResMut<SetTimings>| timings.record(i)).after(*set);
+            match GAME_SETS.get(i + 1) {
+                Some((_, next)) => app.add_systems(Update, probe.before(*next)),
+                None => app.add_systems(Update, probe),
+            };
+        }
+    }
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            colliders: false,
+            bullets: false,
+            grid: false,
+            spawn_rings: false,
+            panels: true,
+        }
+    }
+}
+
+impl DebugOverlay {
+    fn panels_display(&self) -> Display {
+        if self.visible && self.panels {
+            Display::Flex
+        } else {
+            Display::None
+        }
+    }
+}
+
+impl SetTimings {
+    fn record(&mut self, set: usize) {
+        let now = Instant::now();
+        let Some(started) = self.started.replace(now) else {
+            return;
+        };
+
+        let millis = (now - started).as_secs_f32() * 1000.0;
+        self.millis[set] = self.millis[set] * 0.9 + millis * 0.1;
+    }
+}
+
+fn start_set_timings(mut timings: ResMut<SetTimings>) {
+    timings.started = Some(Instant::now());
+}
+
+fn toggle_overlay_layers(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+    if !overlay.visible {
+        return;
+    }
+
+    // Borrow the fields directly, marking the resource changed only on a toggle
+    let layers = overlay.bypass_change_detection();
+    let mut toggled = false;
+    for (key, layer) in [
+        (KeyCode::F4, &mut layers.colliders),
+        (KeyCode::F5, &mut layers.bullets),
+        (KeyCode::F6, &mut layers.grid),
+        (KeyCode::F7, &mut layers.spawn_rings),
+        (KeyCode::F8, &mut layers.panels),
+    ] {
+        if keyboard_input.just_pressed(key) {
+            *layer = !*layer;
+            toggled = true;
+        }
+    }
+    if toggled {
+        overlay.set_changed();
+    }
+}
+
+fn spawn_overlay_panels(mut commands: Commands, font: Res<GlobalFont>, overlay: Res<DebugOverlay>) {
+    let text_font = |size: f32| TextFont {
+        font: font.0.clone(),
+        font_size: size,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(HUD_MARGIN),
+                left: Val::Px(HUD_MARGIN),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                display: overlay.panels_display(),
+                ..default()
+            },
+            OverlayPanels,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((Text::new("FPS: "), text_font(42.0)))
+                .with_child((
+                    TextSpan::default(),
+                    text_font(33.0),
+                    TextColor(GOLD.into()),
+                    FpsText,
+                ));
+            let panel = || {
+                (
+                    Text::default(),
+                    text_font(24.0),
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+                )
+            };
+            parent.spawn((panel(), CountsText));
+            parent.spawn((panel(), TimingsText));
+            parent.spawn((panel(), StateText));
+        });
+}
+
+fn show_overlay_panels(
+    overlay: Res<DebugOverlay>,
+    mut panels_query: Query<&mut Node, With<OverlayPanels>>,
+) {
+    let display = overlay.panels_display();
+    for mut node in panels_query.iter_mut() {
+        node.display = display;
+    }
+}
+
+fn update_fps_text(
+    diagnostics: Res<DiagnosticsStore>,
+    mut query: Query<&mut TextSpan, With<FpsText>>,
+) {
+    let Some(fps) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    else {
+        return;
+    };
+
+    for mut span in query.iter_mut() {
+        **span = format!("{fps:.2}");
+    }
+}
+
+type EntityKinds = (
+    Has<Enemy>,
+    Has<Dying>,
+    Has<Bullet>,
+    Has<Particle>,
+    Has<Corpse>,
+    Has<DamageNumber>,
+    Has<Pooled>,
+);
+
+fn update_counts_text(
+    entities: &Entities,
+    kind_query: Query<EntityKinds>,
+    mut text_query: Query<&mut Text, With<CountsText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let mut counts = [0; 7];
+    for kinds in kind_query.iter() {
+        let kinds: [bool; 7] = kinds.into();
+        for (count, has) in counts.iter_mut().zip(kinds) {
+            *count += has as usize;
+        }
+    }
+    let [enemies, dying, bullets, particles, corpses, numbers, pooled] = counts;
+
+    text.0 = format!(
+        "enemies {}  dying {}\nbullets {}  particles {}\ncorpses {}  numbers {}\npooled {}  total {}",
+        enemies - dying,
+        dying,
+        bullets,
+        particles,
+        corpses,
+        numbers,
+        pooled,
+        entities.len(),
+    );
+}
+
+fn update_timings_text(
+    timings: Res<SetTimings>,
+    mut text_query: Query<&mut Text, With<TimingsText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let rows = GAME_SETS
+        .iter()
+        .zip(timings.millis)
+        .map(|((name, _), millis)| format!("{name:<10}{millis:>6.2} ms"));
+    text.0 = std::iter::once("per set, approximate".to_string())
+        .chain(rows)
+        .collect::<Vec<_>>()
+        .join("\n");
+}
+
+fn update_state_text(
+    game_state: Res<State<GameState>>,
+    camera_mode: Res<State<CameraMode>>,
+    mut text_query: Query<&mut Text, With<StateText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    text.0 = format!(
+        "state {:?}\ncamera {:?}",
+        game_state.get(),
+        camera_mode.get()
+    );
+}
+
+/// World-space rectangle the camera currently sees, to skip drawing
+/// gizmos for the thousands of enemies off-screen.
+fn camera_view(
+    camera_query: &Query<(&Transform, &OrthographicProjection), With<Camera>>,
+) -> Option<Rect> {
+    let (transform, projection) = camera_query.get_single().ok()?;
+    let center = transform.translation.truncate();
+    Some(Rect::from_center_half_size(
+        center,
+        projection.area.half_size(),
+    ))
+}
+
+fn draw_colliders(
+    mut gizmos: Gizmos,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    collider_query: Query<(&Transform, &Collider, Has<Player>)>,
+) {
+    let Some(view) = camera_view(&camera_query) else {
+        return;
+    };
+
+    for (transform, collider, is_player) in collider_query.iter() {
+        let pos = transform.translation.truncate();
+        if !view.contains(pos) {
+            continue;
+        }
+
+        let color = if is_player { LIME } else { RED };
+        match collider.shape {
+            ColliderShape::Circle { radius } => {
+                gizmos.circle_2d(Isometry2d::from_translation(pos), radius, color);
+            }
+            ColliderShape::Aabb { half_extents } => {
+                gizmos.rect_2d(Isometry2d::from_translation(pos), half_extents * 2.0, color);
+            }
+        }
+    }
+}
+
+fn draw_bullet_directions(
+    mut gizmos: Gizmos,
+    bullet_query: Query<(&Transform, &BulletDirection), With<Bullet>>,
+) {
+    for (transform, direction) in bullet_query.iter() {
+        let start = transform.translation.truncate();
+        let dir = direction.0.truncate().normalize_or_zero();
+        // Where the bullet will be four 60 fps frames from now
+        gizmos.arrow_2d(start, start + dir * BULLET_SPEED * 4.0 / 60.0, YELLOW);
+    }
+}
+
+fn draw_spatial_grid(
+    mut gizmos: Gizmos,
+    grid: Res<SpatialGrid>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+) {
+    let Some(view) = camera_view(&camera_query) else {
+        return;
+    };
+
+    let cell_size = grid.cell_size();
+    for cell in grid.occupied_cells() {
+        let center = (cell.as_vec2() + 0.5) * cell_size;
+        if !view.contains(center) {
+            continue;
+        }
+        gizmos.rect_2d(
+            Isometry2d::from_translation(center),
+            Vec2::splat(cell_size),
+            SKY_BLUE.with_alpha(0.4),
+        );
+    }
+}
+
+fn draw_spawn_rings(mut gizmos: Gizmos, player_query: Query<&Transform, With<Player>>) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+
+    let isometry = Isometry2d::from_translation(transform.translation.truncate());
+    gizmos.circle_2d(isometry, ENEMY_SPAWN_MIN_DISTANCE, ORANGE);
+    gizmos.circle_2d(isometry, ENEMY_SPAWN_MAX_DISTANCE, ORANGE);
+    gizmos.circle_2d(isometry, ENEMY_LEASH_DISTANCE, RED);
+}
@@ -1,93 +1,196 @@
-use std::{f32::consts::PI, time::Duration};
-
-use bevy::{
-    math::{vec2, vec3},
-    prelude::*,
-    state::commands,
-    time::common_conditions::on_timer,
-    transform,
-};
+use std::{f32::consts::PI, ops::Range, time::Duration};
+
+use bevy::{core::FrameCount, math::vec2, prelude::*, time::common_conditions::on_timer};
 use rand::Rng;
 
 use crate::{
-    animation::AnimationTimer, player::Player, GameState, GlobalTextureAtlas, ENEMY_HEALTH,
-    ENEMY_SPAWN_INTERVAL, ENEMY_SPEED, MAX_NUM_ENEMIES, SPAWN_RATE_PER_SECOND, SPRITE_SCALE_FACTOR,
-    WORLD_H, WORLD_W,
+    animation::AnimationTimer,
+    biome::BiomeMap,
+    collision::{Collider, CollisionLayers},
+    console::{console_arg, ConsoleAppExt, ConsoleArgs, ConsoleResult},
+    death::Dying,
+    events::{BulletHit, EnemyDamaged, EnemyKilled},
+    lod::EnemyLod,
+    player::{Player, PlayerMovement},
+    pool::EnemyPool,
+    progression::Wave,
+    GameSet, GameState, GlobalTextureAtlas, Tuning, ENEMY_COLLIDER_RADIUS, ENEMY_HEALTH,
+    ENEMY_HIT_FLASH_INTENSITY, ENEMY_HIT_FLASH_SECS, ENEMY_KNOCKBACK_DAMPING, ENEMY_SPAWN_INTERVAL,
+    ENEMY_SPAWN_MAX_DISTANCE, ENEMY_SPAWN_MIN_DISTANCE, MAX_NUM_ENEMIES, SPRITE_SCALE_FACTOR,
 };
 
 pub struct EnemyPlugin;
 
 #[derive(Component)]
+#[require(Knockback)]
 pub struct Enemy {
     pub health: f32,
+    pub kind: EnemyKind,
+    /// Source of the most recent damage, reported as the killer.
+    pub last_hit_by: Option<Entity>,
+}
+
+/// Query filter for enemies still in the fight, not playing their death clip.
+pub type LiveEnemy = (With<Enemy>, Without<Dying>);
+
+/// Velocity from hits in world units per second, added on top of chasing and
+/// decaying back to zero.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Knockback(pub Vec2);
+
+/// Tints the sprite white until the timer runs out.
+#[derive(Component, Deref, DerefMut)]
+pub struct HitFlash(pub Timer);
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum EnemyKind {
+    #[default]
+    Zombie,
+    Ghoul,
+    Slime,
+    Brute,
 }
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.add_console_command(
+            "spawn",
+            "spawn <kind> [n]",
+            &EnemyKind::ALL.map(|kind| kind.name()),
+            spawn_command,
+        )
+        .add_console_command("kill_all", "kill_all", &[], kill_all_command)
+        .add_systems(
             Update,
             (
-                spawn_enemies.run_if(on_timer(Duration::from_secs_f32(ENEMY_SPAWN_INTERVAL))),
-                update_enemy_transform,
-                despawn_dead_enemies,
+                spawn_enemies
+                    .in_set(GameSet::Cleanup)
+                    .run_if(on_timer(Duration::from_secs_f32(ENEMY_SPAWN_INTERVAL))),
+                update_enemy_transform
+                    .in_set(GameSet::Movement)
+                    .after(PlayerMovement),
+                apply_bullet_hits.in_set(GameSet::Damage),
+                update_hit_flash.in_set(GameSet::Animation),
+                kill_dead_enemies
+                    .in_set(GameSet::Cleanup)
+                    .before(spawn_enemies),
             )
                 .run_if(in_state(GameState::InGame)),
         );
     }
 }
 
-fn update_enemy_transform(
+type EnemyMotion = (
+    Entity,
+    &'static mut Transform,
+    &'static mut Knockback,
+    &'static EnemyLod,
+);
+
+pub fn update_enemy_transform(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    biome_map: Res<BiomeMap>,
+    frame_count: Res<FrameCount>,
     player_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<&mut Transform, (With<Enemy>, Without<Player>)>,
+    mut enemy_query: Query<EnemyMotion, (LiveEnemy, Without<Player>)>,
 ) {
     if player_query.is_empty() || enemy_query.is_empty() {
         return;
     }
 
     let player_pos = player_query.single().translation;
-    for mut transform in enemy_query.iter_mut() {
-        let dir = (player_pos - transform.translation).normalize();
-        transform.translation += dir * ENEMY_SPEED;
-        transform.translation.z = 10.0;
-    }
+    let dt = time.delta_secs();
+    enemy_query
+        .par_iter_mut()
+        .for_each(|(entity, mut transform, mut knockback, lod)| {
+            let Some(step) = lod.movement_step(entity, frame_count.0) else {
+                return;
+            };
+            let step = step * dt;
+
+            let dir = (player_pos - transform.translation).normalize();
+            let speed_multiplier = biome_map.speed_multiplier_at(transform.translation.truncate());
+            transform.translation += dir * tuning.enemy_speed * speed_multiplier * step;
+            transform.translation.z = 10.0;
+
+            if knockback.0 != Vec2::ZERO {
+                transform.translation += knockback.extend(0.0) * step;
+                knockback.0 *= (-ENEMY_KNOCKBACK_DAMPING * step).exp();
+                if knockback.length_squared() < 1.0 {
+                    knockback.0 = Vec2::ZERO;
+                }
+            }
+        });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_enemies(
     mut commands: Commands,
+    mut enemy_pool: ResMut<EnemyPool>,
     handle: Res<GlobalTextureAtlas>,
+    biome_map: Res<BiomeMap>,
+    wave: Res<Wave>,
+    tuning: Res<Tuning>,
     player_query: Query<&Transform, With<Player>>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    enemy_query: Query<(), LiveEnemy>,
 ) {
     let num_enemies: u32 = enemy_query.iter().len() as u32;
-    let enemy_spawn_count = (MAX_NUM_ENEMIES - num_enemies).min(SPAWN_RATE_PER_SECOND);
-
     if num_enemies >= MAX_NUM_ENEMIES || player_query.is_empty() {
         return;
     }
+    let enemy_spawn_count = (MAX_NUM_ENEMIES - num_enemies).min(wave.spawn_rate(&tuning));
 
+    let mut rng = rand::rng();
     let player_pos = player_query.single().translation.truncate();
     for _ in 0..enemy_spawn_count {
         let (x, y) = get_random_position_around(player_pos);
-        commands.spawn((
+        let kind = biome_map
+            .biome_at(vec2(x, y))
+            .def()
+            .random_enemy_kind(&mut rng);
+        spawn_enemy(&mut commands, &mut enemy_pool, &handle, kind, vec2(x, y));
+    }
+}
+
+fn spawn_enemy(
+    commands: &mut Commands,
+    enemy_pool: &mut EnemyPool,
+    handle: &GlobalTextureAtlas,
+    kind: EnemyKind,
+    pos: Vec2,
+) {
+    enemy_pool.acquire(
+        commands,
+        (
             Sprite::from_atlas_image(
                 handle.image.clone().unwrap(),
                 TextureAtlas {
                     layout: handle.layout.clone().unwrap(),
-                    index: 12,
+                    index: kind.base_sprite_index(),
                 },
             ),
-            Transform::from_translation(vec3(x, y, 1.0))
+            Transform::from_translation(pos.extend(1.0))
                 .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            Enemy::default(),
+            Enemy::new(kind),
+            EnemyLod::default(),
+            Collider::circle(ENEMY_COLLIDER_RADIUS).with_layers(
+                CollisionLayers::ENEMY,
+                CollisionLayers::PLAYER | CollisionLayers::BULLET,
+            ),
             AnimationTimer(Timer::from_seconds(0.08, TimerMode::Repeating)),
-        ));
-    }
+        ),
+    );
 }
 
 fn get_random_position_around(pos: Vec2) -> (f32, f32) {
+    get_random_position_in_ring(pos, ENEMY_SPAWN_MIN_DISTANCE..ENEMY_SPAWN_MAX_DISTANCE)
+}
+
+pub fn get_random_position_in_ring(pos: Vec2, distance: Range<f32>) -> (f32, f32) {
     let mut rng = rand::rng();
     let angle = rng.random_range(0.0..PI * 2.0);
-    let dist = rng.random_range(100.0..2000.0);
+    let dist = rng.random_range(distance);
 
     let offset_x = angle.cos() * dist;
     let offset_y = angle.sin() * dist;
@@ -98,25 +201,204 @@ fn get_random_position_around(pos: Vec2) -> (f32, f32) {
     (random_x, random_y)
 }
 
-fn despawn_dead_enemies(
+fn apply_bullet_hits(
+    mut commands: Commands,
+    mut hit_events: EventReader<BulletHit>,
+    mut enemy_query: Query<(&mut Enemy, &mut Knockback, &Transform), With<Enemy>>,
+    mut damaged_events: EventWriter<EnemyDamaged>,
+) {
+    for hit in hit_events.read() {
+        let Ok((mut enemy, mut knockback, transform)) = enemy_query.get_mut(hit.target) else {
+            continue;
+        };
+        // Already dead, waiting to be despawned
+        if enemy.health <= 0.0 {
+            continue;
+        }
+
+        enemy.health -= hit.damage;
+        enemy.last_hit_by = Some(hit.shooter);
+        knockback.0 += hit.direction * hit.knockback / enemy.kind.mass();
+        commands
+            .entity(hit.target)
+            .insert(HitFlash(Timer::from_seconds(
+                ENEMY_HIT_FLASH_SECS,
+                TimerMode::Once,
+            )));
+        damaged_events.send(EnemyDamaged {
+            enemy: hit.target,
+            amount: hit.damage,
+            position: transform.translation.truncate(),
+            source: Some(hit.shooter),
+            critical: hit.critical,
+        });
+    }
+}
+
+fn update_hit_flash(
     mut commands: Commands,
-    mut enemy_query: Query<(&Enemy, Entity), With<Enemy>>,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in flash_query.iter_mut() {
+        if flash.tick(time.delta()).finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            sprite.color = Color::srgb(
+                ENEMY_HIT_FLASH_INTENSITY,
+                ENEMY_HIT_FLASH_INTENSITY,
+                ENEMY_HIT_FLASH_INTENSITY,
+            );
+        }
+    }
+}
+
+fn kill_dead_enemies(
+    mut commands: Commands,
+    mut enemy_query: Query<(&Enemy, &Transform, Entity), Without<Dying>>,
+    mut killed_events: EventWriter<EnemyKilled>,
 ) {
     if enemy_query.is_empty() {
         return;
     }
 
-    for (enemy, entity) in enemy_query.iter_mut() {
+    for (enemy, transform, entity) in enemy_query.iter_mut() {
         if enemy.health <= 0.0 {
-            commands.entity(entity).despawn();
+            killed_events.send(EnemyKilled {
+                kind: enemy.kind,
+                position: transform.translation.truncate(),
+                killer: enemy.last_hit_by,
+            });
+            // The death clip takes it from here and returns it to the pool
+            commands
+                .entity(entity)
+                .remove::<Collider>()
+                .insert(Dying::new());
         }
     }
 }
 
-impl Default for Enemy {
-    fn default() -> Self {
+fn spawn_command(
+    In(args): In<ConsoleArgs>,
+    mut commands: Commands,
+    mut enemy_pool: ResMut<EnemyPool>,
+    handle: Res<GlobalTextureAtlas>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<(), LiveEnemy>,
+) -> ConsoleResult {
+    let name = args.first().ok_or("usage: spawn <kind> [n]")?;
+    let kind = EnemyKind::ALL
+        .into_iter()
+        .find(|kind| kind.name() == name)
+        .ok_or_else(|| format!("unknown enemy kind: {name}"))?;
+    // Never past the enemy cap, which the spawner counts on
+    let capacity = MAX_NUM_ENEMIES.saturating_sub(enemy_query.iter().len() as u32);
+    let count = console_arg(&args, 1, 1)?.min(capacity);
+    let player_pos = player_query
+        .get_single()
+        .map_err(|_| "no player")?
+        .translation
+        .truncate();
+
+    for _ in 0..count {
+        let (x, y) = get_random_position_around(player_pos);
+        spawn_enemy(&mut commands, &mut enemy_pool, &handle, kind, vec2(x, y));
+    }
+    Ok(format!("spawned {count} {name}"))
+}
+
+/// Drops every living enemy to zero health, so kills still count.
+fn kill_all_command(
+    In(_): In<ConsoleArgs>,
+    mut enemy_query: Query<&mut Enemy, Without<Dying>>,
+) -> ConsoleResult {
+    let mut count = 0;
+    for mut enemy in enemy_query.iter_mut() {
+        enemy.health = 0.0;
+        count += 1;
+    }
+    Ok(format!("killed {count} enemies"))
+}
+
+impl Enemy {
+    pub fn new(kind: EnemyKind) -> Self {
         Self {
-            health: ENEMY_HEALTH,
+            health: kind.max_health(),
+            kind,
+            last_hit_by: None,
         }
     }
 }
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [
+        EnemyKind::Zombie,
+        EnemyKind::Ghoul,
+        EnemyKind::Slime,
+        EnemyKind::Brute,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Zombie => "zombie",
+            EnemyKind::Ghoul => "ghoul",
+            EnemyKind::Slime => "slime",
+            EnemyKind::Brute => "brute",
+        }
+    }
+
+    /// First frame of the kind's four-frame walk cycle in the sprite sheet.
+    pub fn base_sprite_index(&self) -> usize {
+        match self {
+            EnemyKind::Zombie => 8,
+            EnemyKind::Ghoul => 12,
+            EnemyKind::Slime => 20,
+            EnemyKind::Brute => 28,
+        }
+    }
+
+    /// Frames of the death clip. The sheet has no dedicated death art yet, so
+    /// this is the walk cycle played once while the body topples.
+    pub fn death_frames(&self) -> [usize; 4] {
+        let base = self.base_sprite_index();
+        [base, base + 1, base + 2, base + 3]
+    }
+
+    /// Experience for killing one, score is proportional to it.
+    pub fn xp(&self) -> u32 {
+        match self {
+            EnemyKind::Zombie | EnemyKind::Slime => 1,
+            EnemyKind::Ghoul => 2,
+            EnemyKind::Brute => 5,
+        }
+    }
+
+    /// Zombies take a few pellets and brutes soak up several shots, so hits
+    /// show knockback and flashes before the kill.
+    pub fn max_health(&self) -> f32 {
+        ENEMY_HEALTH
+            * match self {
+                EnemyKind::Zombie => 1.0,
+                EnemyKind::Ghoul => 0.7,
+                EnemyKind::Slime => 0.5,
+                EnemyKind::Brute => 5.0,
+            }
+    }
+
+    /// Divides incoming knockback, so heavy kinds barely budge.
+    pub fn mass(&self) -> f32 {
+        match self {
+            EnemyKind::Zombie => 1.0,
+            EnemyKind::Ghoul => 0.8,
+            EnemyKind::Slime => 0.6,
+            EnemyKind::Brute => 4.0,
+        }
+    }
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Self::new(EnemyKind::default())
+    }
+}
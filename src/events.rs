@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::{enemy::EnemyKind, pickups::PickupKind};
+
+pub struct GameEventsPlugin;
+
+/// A bullet struck something on its path this frame. The bullet is already
+/// back in the pool when this is read.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BulletHit {
+    pub bullet: Entity,
+    /// Whoever fired the bullet. Unlike `bullet`, safe to hold on to.
+    pub shooter: Entity,
+    pub target: Entity,
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub damage: f32,
+    /// Impulse along `direction` for a unit-mass target.
+    pub knockback: f32,
+    pub critical: bool,
+}
+
+/// The gun fired a shot from `position`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GunFired {
+    pub position: Vec2,
+    pub direction: Vec2,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct EnemyDamaged {
+    pub enemy: Entity,
+    pub amount: f32,
+    pub position: Vec2,
+    pub source: Option<Entity>,
+    pub critical: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct EnemyKilled {
+    pub kind: EnemyKind,
+    pub position: Vec2,
+    /// Whoever dealt the final blow, usually the player.
+    pub killer: Option<Entity>,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerDamaged {
+    pub amount: f32,
+    pub source: Entity,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerDied {
+    pub position: Vec2,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayerLeveledUp {
+    pub level: u32,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    pub position: Vec2,
+}
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BulletHit>()
+            .add_event::<GunFired>()
+            .add_event::<EnemyDamaged>()
+            .add_event::<EnemyKilled>()
+            .add_event::<PlayerDamaged>()
+            .add_event::<PlayerDied>()
+            .add_event::<PlayerLeveledUp>()
+            .add_event::<PickupCollected>();
+    }
+}
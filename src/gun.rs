@@ -1,11 +1,19 @@
 use std::f32::consts::PI;
-use std::time::Instant;
+use std::time::Duration;
 
-use crate::player::Player;
-use crate::resources::{CursorPosition, GlobalTextureAtlas};
+use crate::collision::{Collider, CollisionLayers, PreviousPosition};
+use crate::console::{ConsoleAppExt, ConsoleArgs, ConsoleResult};
+use crate::events::GunFired;
+use crate::particles::ParticleEmitter;
+use crate::pickups::PowerUps;
+use crate::player::{Player, PlayerMovement};
+use crate::pool::BulletPool;
+use crate::resources::{CursorPosition, GlobalTextureAtlas, Tuning};
+use crate::settings::Settings;
 use crate::*;
 use bevy::math::{vec2, vec3};
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
 use bevy::time::Stopwatch;
 use rand::Rng;
 
@@ -16,95 +24,265 @@ pub struct Gun;
 #[derive(Component)]
 pub struct GunTimer(pub Stopwatch);
 
+/// Magazine and reserve of a gun that has to reload. Guns without one never
+/// run dry, as in the survivor-style mode.
+#[derive(Component)]
+pub struct Ammo {
+    pub magazine: u32,
+    pub magazine_size: u32,
+    /// Rounds left to reload from, `None` for an endless supply.
+    pub reserve: Option<u32>,
+    pub reload_secs: f32,
+}
+
+/// The gun can't fire until the timer finishes and the magazine is refilled.
+#[derive(Component)]
+pub struct Reloading {
+    pub timer: Timer,
+    /// Progress bar shown above the gun while reloading.
+    bar: Entity,
+}
+
+#[derive(Component)]
+struct ReloadBarFill;
+
 #[derive(Component)]
 pub struct Bullet;
 
+/// Game time the bullet was fired at, so lifetimes follow pauses and `timescale`.
+#[derive(Component)]
+pub struct SpawnInstant(pub Duration);
+
 #[derive(Component)]
-pub struct SpawnInstant(Instant);
+pub struct BulletDirection(pub Vec3);
 
+/// Whoever fired the bullet, credited with what it hits.
 #[derive(Component)]
-pub struct BulletDirection(Vec3);
+pub struct Shooter(pub Entity);
 
 impl Plugin for GunPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.add_console_command(
+            "give",
+            "give <weapon>",
+            &[GUN_NAME.to_lowercase().as_str()],
+            give_command,
+        )
+        .add_console_command("infinite_ammo", "infinite_ammo", &[], infinite_ammo_command)
+        .add_systems(
             Update,
             (
-                handle_gun_input,
-                update_bullets,
-                update_gun_transform,
-                despawn_old_bullets,
+                (reload_gun, handle_gun_input)
+                    .chain()
+                    .in_set(GameSet::Input),
+                (
+                    update_bullets,
+                    (update_gun_transform, update_reload_bar)
+                        .chain()
+                        .after(PlayerMovement),
+                )
+                    .in_set(GameSet::Movement),
+                despawn_old_bullets.in_set(GameSet::Cleanup),
             )
                 .run_if(in_state(GameState::InGame)),
         );
     }
 }
 
+impl Ammo {
+    pub fn shotgun() -> Self {
+        Self {
+            magazine: GUN_MAGAZINE_SIZE,
+            magazine_size: GUN_MAGAZINE_SIZE,
+            reserve: Some(GUN_START_RESERVE),
+            reload_secs: GUN_RELOAD_SECS,
+        }
+    }
+
+    /// Whether a reload would put any rounds in the magazine.
+    pub fn can_reload(&self) -> bool {
+        self.magazine < self.magazine_size && self.reserve != Some(0)
+    }
+
+    /// Moves as many rounds as fit from the reserve into the magazine.
+    pub fn refill_magazine(&mut self) {
+        let missing = self.magazine_size - self.magazine;
+        let loaded = self.reserve.map_or(missing, |reserve| reserve.min(missing));
+        self.magazine += loaded;
+        if let Some(reserve) = &mut self.reserve {
+            *reserve -= loaded;
+        }
+    }
+
+    /// Adds rounds to a limited reserve, up to [`GUN_MAX_RESERVE`].
+    pub fn add_reserve(&mut self, rounds: u32) {
+        if let Some(reserve) = &mut self.reserve {
+            *reserve = (*reserve + rounds).min(GUN_MAX_RESERVE);
+        }
+    }
+}
+
 fn despawn_old_bullets(
     mut commands: Commands,
+    time: Res<Time>,
+    mut bullet_pool: ResMut<BulletPool>,
     mut bullet_query: Query<(&SpawnInstant, Entity), With<Bullet>>,
 ) {
     for (instant, entity) in bullet_query.iter_mut() {
-        if instant.0.elapsed().as_secs_f32() > BULLET_LIFE_TIME_IN_SECS {
-            commands.entity(entity).despawn();
+        if (time.elapsed() - instant.0).as_secs_f32() > BULLET_LIFE_TIME_IN_SECS {
+            bullet_pool.release(&mut commands, entity);
         }
     }
 }
 
+type ReadyGun = (With<Gun>, Without<Reloading>);
+
+#[allow(clippy::too_many_arguments)]
 fn handle_gun_input(
     mut commands: Commands,
+    mut bullet_pool: ResMut<BulletPool>,
     time: Res<Time>,
-    mut gun_query: Query<(&Transform, &mut GunTimer), With<Gun>>,
+    tuning: Res<Tuning>,
+    mut gun_query: Query<(&Transform, &mut GunTimer, Option<&mut Ammo>), ReadyGun>,
+    player_query: Query<(Entity, &PowerUps), With<Player>>,
     handle: Res<GlobalTextureAtlas>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
+    mut fired_events: EventWriter<GunFired>,
 ) {
-    if gun_query.is_empty() {
+    let Ok((gun_transform, mut gun_timer, mut ammo)) = gun_query.get_single_mut() else {
         return;
-    }
+    };
 
-    let (gun_transform, mut gun_timer) = gun_query.single_mut();
     let gun_pos = gun_transform.translation.truncate();
     gun_timer.0.tick(time.delta());
 
-    if !mouse_button_input.pressed(MouseButton::Left) {
+    if !mouse_button_input.pressed(MouseButton::Left)
+        || ammo.as_ref().is_some_and(|ammo| ammo.magazine == 0)
+    {
         return;
     }
 
+    let Ok((player, power_ups)) = player_query.get_single() else {
+        return;
+    };
+
     let mut rng = rand::rng();
     let bullet_direction = gun_transform.local_x();
-    if gun_timer.0.elapsed_secs() >= BULLET_SPAWN_INTERVAL {
+    let interval = tuning.fire_interval * power_ups.fire_interval_multiplier();
+    if gun_timer.0.elapsed_secs() >= interval {
         gun_timer.0.reset();
+        if let Some(ammo) = &mut ammo {
+            ammo.magazine -= 1;
+        }
+        let muzzle_dir = bullet_direction.truncate();
+        let muzzle_pos = gun_pos + muzzle_dir * GUN_MUZZLE_OFFSET;
+        fired_events.send(GunFired {
+            position: muzzle_pos,
+            direction: muzzle_dir,
+        });
+        commands.spawn((
+            ParticleEmitter::muzzle_flash(muzzle_dir),
+            Transform::from_translation(muzzle_pos.extend(12.0)),
+        ));
 
-        for _ in 0..NUM_OF_BULLET_PER_SHOT {
+        for _ in 0..tuning.bullets_per_shot {
             let dir = vec3(
                 bullet_direction.x + rng.random_range(-1.0..1.0),
                 bullet_direction.y + rng.random_range(-1.0..1.0),
                 bullet_direction.z,
             );
-            commands.spawn((
-                Sprite::from_atlas_image(
-                    handle.image.clone().unwrap(),
-                    TextureAtlas {
-                        layout: handle.layout.clone().unwrap(),
-                        index: 16,
-                    },
+            bullet_pool.acquire(
+                &mut commands,
+                (
+                    Sprite::from_atlas_image(
+                        handle.image.clone().unwrap(),
+                        TextureAtlas {
+                            layout: handle.layout.clone().unwrap(),
+                            index: 16,
+                        },
+                    ),
+                    Transform::from_translation(vec3(gun_pos.x, gun_pos.y, 11.0)),
+                    Bullet,
+                    BulletDirection(dir),
+                    Shooter(player),
+                    SpawnInstant(time.elapsed()),
+                    PreviousPosition(gun_pos),
+                    Collider::circle(BULLET_COLLIDER_RADIUS).with_layers(
+                        CollisionLayers::BULLET,
+                        CollisionLayers::ENEMY | CollisionLayers::OBSTACLE,
+                    ),
                 ),
-                Transform::from_translation(vec3(gun_pos.x, gun_pos.y, 11.0)),
-                Bullet,
-                BulletDirection(dir),
-                SpawnInstant(Instant::now()),
-            ));
+            );
+        }
+    }
+}
+
+/// Reloads on the reload key, or by itself once the magazine is empty.
+fn reload_gun(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut gun_query: Query<(Entity, &mut Ammo, Option<&mut Reloading>), With<Gun>>,
+) {
+    let Ok((entity, mut ammo, reloading)) = gun_query.get_single_mut() else {
+        return;
+    };
+
+    if let Some(mut reloading) = reloading {
+        if reloading.timer.tick(time.delta()).finished() {
+            ammo.refill_magazine();
+            commands.entity(reloading.bar).despawn_recursive();
+            commands.entity(entity).remove::<Reloading>();
         }
+        return;
+    }
+
+    let wants_reload = keyboard_input.just_pressed(settings.keys.reload) || ammo.magazine == 0;
+    if !wants_reload || !ammo.can_reload() {
+        return;
     }
+
+    let bar = commands
+        .spawn((
+            Sprite::from_color(
+                Color::srgba(0.0, 0.0, 0.0, 0.6),
+                vec2(RELOAD_BAR_WIDTH, RELOAD_BAR_HEIGHT),
+            ),
+            Transform::default(),
+        ))
+        .with_child((
+            Sprite {
+                color: Color::srgb(1.0, 0.85, 0.3),
+                custom_size: Some(vec2(0.0, RELOAD_BAR_HEIGHT)),
+                anchor: Anchor::CenterLeft,
+                ..default()
+            },
+            Transform::from_xyz(-RELOAD_BAR_WIDTH / 2.0, 0.0, 0.1),
+            ReloadBarFill,
+        ))
+        .id();
+    commands.entity(entity).insert(Reloading {
+        timer: Timer::from_seconds(ammo.reload_secs, TimerMode::Once),
+        bar,
+    });
 }
 
-fn update_bullets(mut bullet_query: Query<(&mut Transform, &BulletDirection), With<Bullet>>) {
+fn update_bullets(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    mut bullet_query: Query<
+        (&mut Transform, &mut PreviousPosition, &BulletDirection),
+        With<Bullet>,
+    >,
+) {
     if bullet_query.is_empty() {
         return;
     }
 
-    for (mut transform, direction) in bullet_query.iter_mut() {
-        transform.translation += direction.0.normalize() * Vec3::splat(BULLET_SPEED);
+    for (mut transform, mut previous, direction) in bullet_query.iter_mut() {
+        previous.0 = transform.translation.truncate();
+        transform.translation += direction.0.normalize() * tuning.bullet_speed * time.delta_secs();
         transform.translation.z = 10.0;
     }
 }
@@ -136,3 +314,71 @@ fn update_gun_transform(
     gun_transform.translation = vec3(new_gun_pos.x, new_gun_pos.y, gun_transform.translation.z);
     gun_transform.translation.z = 10.0;
 }
+
+/// Keeps the reload bar above the gun, filled as far as the reload has come.
+fn update_reload_bar(
+    gun_query: Query<(&Transform, &Reloading), With<Gun>>,
+    mut bar_query: Query<&mut Transform, Without<Gun>>,
+    mut fill_query: Query<(&Parent, &mut Sprite), With<ReloadBarFill>>,
+) {
+    let Ok((gun_transform, reloading)) = gun_query.get_single() else {
+        return;
+    };
+
+    if let Ok(mut transform) = bar_query.get_mut(reloading.bar) {
+        transform.translation = gun_transform.translation + vec3(0.0, RELOAD_BAR_OFFSET, 3.0);
+    }
+    for (parent, mut sprite) in fill_query.iter_mut() {
+        if parent.get() == reloading.bar {
+            sprite.custom_size = Some(vec2(
+                RELOAD_BAR_WIDTH * reloading.timer.fraction(),
+                RELOAD_BAR_HEIGHT,
+            ));
+        }
+    }
+}
+
+/// There is only the one gun so far; giving it again readies it to fire with
+/// a full magazine and reserve.
+fn give_command(
+    In(args): In<ConsoleArgs>,
+    mut gun_query: Query<(&mut GunTimer, Option<&mut Ammo>), With<Gun>>,
+) -> ConsoleResult {
+    let name = args.first().ok_or("usage: give <weapon>")?;
+    if !name.eq_ignore_ascii_case(GUN_NAME) {
+        return Err(format!("unknown weapon: {name}"));
+    }
+
+    let (mut gun_timer, ammo) = gun_query.get_single_mut().map_err(|_| "no gun")?;
+    gun_timer
+        .0
+        .set_elapsed(Duration::from_secs_f32(BULLET_SPAWN_INTERVAL));
+    if let Some(mut ammo) = ammo {
+        ammo.magazine = ammo.magazine_size;
+        ammo.add_reserve(GUN_MAX_RESERVE);
+    }
+    Ok(format!("gave {GUN_NAME}"))
+}
+
+type GunAmmo = (Entity, Option<&'static Reloading>, Has<Ammo>);
+
+/// Takes the magazine off the gun so it never needs reloading, or puts a full
+/// one back.
+fn infinite_ammo_command(
+    In(_): In<ConsoleArgs>,
+    mut commands: Commands,
+    gun_query: Query<GunAmmo, With<Gun>>,
+) -> ConsoleResult {
+    let (entity, reloading, has_ammo) = gun_query.get_single().map_err(|_| "no gun")?;
+    if let Some(reloading) = reloading {
+        commands.entity(reloading.bar).despawn_recursive();
+    }
+    let mut gun = commands.entity(entity);
+    gun.remove::<Reloading>();
+    if has_ammo {
+        gun.remove::<Ammo>();
+    } else {
+        gun.insert(Ammo::shotgun());
+    }
+    Ok(format!("infinite_ammo = {has_ammo}"))
+}
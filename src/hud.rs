@@ -0,0 +1,360 @@
+use bevy::prelude::*;
+
+use crate::{
+    dash::Dash,
+    gun::{Ammo, Gun, GunTimer, Reloading},
+    pickups::{PowerUp, PowerUps},
+    player::{Health, Player},
+    progression::{Experience, RunStats, Wave},
+    *,
+};
+
+pub struct HudPlugin;
+
+#[derive(Component)]
+struct HealthBarFill;
+
+#[derive(Component)]
+struct XpBarFill;
+
+#[derive(Component)]
+struct LevelText;
+
+/// Time left on one power-up, hidden while it is inactive.
+#[derive(Component)]
+struct PowerUpText(PowerUp);
+
+#[derive(Component)]
+struct DashBarFill;
+
+#[derive(Component)]
+struct DashText;
+
+#[derive(Component)]
+struct AmmoText;
+
+#[derive(Component)]
+struct CooldownBarFill;
+
+#[derive(Component)]
+struct WaveText;
+
+#[derive(Component)]
+struct ScoreText;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameInit), spawn_hud)
+            .add_systems(
+                Update,
+                (
+                    update_health_bar,
+                    update_experience,
+                    update_power_ups,
+                    update_dash,
+                    update_gun_cooldown,
+                    update_ammo_text,
+                    update_wave_text,
+                    update_score_text.run_if(resource_changed::<RunStats>),
+                )
+                    .in_set(GameSet::Ui)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn spawn_hud(mut commands: Commands, font: Res<GlobalFont>) {
+    let text_font = |size: f32| TextFont {
+        font: font.0.clone(),
+        font_size: size,
+        ..default()
+    };
+
+    // Wave, score and kills, top centre
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(HUD_MARGIN),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((Text::default(), text_font(40.0), WaveText));
+            parent.spawn((
+                Text::default(),
+                text_font(30.0),
+                TextColor(Color::srgb(1.0, 0.85, 0.3)),
+                ScoreText,
+            ));
+        });
+
+    // Active power-ups, health, experience, level and dash, bottom left
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(HUD_MARGIN),
+            bottom: Val::Px(HUD_MARGIN),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(6.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            for power_up in PowerUp::ALL {
+                parent.spawn((
+                    Text::default(),
+                    text_font(24.0),
+                    TextColor(power_up.color()),
+                    Node {
+                        display: Display::None,
+                        ..default()
+                    },
+                    PowerUpText(power_up),
+                ));
+            }
+            spawn_bar(parent, 14.0, Color::srgb(0.85, 0.15, 0.15), HealthBarFill);
+            parent
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_bar(parent, 8.0, Color::srgb(0.3, 0.6, 1.0), XpBarFill);
+                    parent.spawn((Text::default(), text_font(28.0), LevelText));
+                });
+            parent
+                .spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_bar(parent, 6.0, Color::srgb(0.6, 0.8, 1.0), DashBarFill);
+                    parent.spawn((Text::default(), text_font(24.0), DashText));
+                });
+        });
+
+    // Weapon, ammo and cooldown, bottom right
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(HUD_MARGIN),
+            bottom: Val::Px(HUD_MARGIN),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::End,
+            row_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((Text::new(GUN_NAME), text_font(30.0)));
+            parent.spawn((Text::new("Ammo inf"), text_font(26.0), AmmoText));
+            spawn_bar(parent, 6.0, Color::srgb(0.9, 0.9, 0.9), CooldownBarFill);
+        });
+}
+
+/// A fixed-width bar whose fill width is set as a percentage.
+fn spawn_bar(parent: &mut ChildBuilder, height: f32, color: Color, fill: impl Component) {
+    parent
+        .spawn((
+            Node {
+                width: Val::Px(HUD_BAR_WIDTH),
+                height: Val::Px(height),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        ))
+        .with_child((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(color),
+            fill,
+        ));
+}
+
+fn set_fill(node: &mut Mut<Node>, fraction: f32) {
+    let width = Val::Percent(fraction.clamp(0.0, 1.0) * 100.0);
+    // Only touch the node when it actually changes, so layout isn't redone every frame
+    if node.width != width {
+        node.width = width;
+    }
+}
+
+fn set_text(text: &mut Mut<Text>, value: String) {
+    if text.0 != value {
+        text.0 = value;
+    }
+}
+
+fn update_health_bar(
+    health_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut fill_query: Query<&mut Node, With<HealthBarFill>>,
+) {
+    let (Ok(health), Ok(mut fill)) = (health_query.get_single(), fill_query.get_single_mut())
+    else {
+        return;
+    };
+
+    set_fill(&mut fill, health.current / health.max);
+}
+
+fn update_experience(
+    experience_query: Query<&Experience, (With<Player>, Changed<Experience>)>,
+    mut fill_query: Query<&mut Node, With<XpBarFill>>,
+    mut level_query: Query<&mut Text, With<LevelText>>,
+) {
+    let Ok(experience) = experience_query.get_single() else {
+        return;
+    };
+
+    if let Ok(mut fill) = fill_query.get_single_mut() {
+        set_fill(
+            &mut fill,
+            experience.xp as f32 / experience.next_level_xp() as f32,
+        );
+    }
+    if let Ok(mut text) = level_query.get_single_mut() {
+        set_text(&mut text, format!("Lv {}", experience.level));
+    }
+}
+
+fn update_power_ups(
+    power_ups_query: Query<&PowerUps, (With<Player>, Changed<PowerUps>)>,
+    mut text_query: Query<(&PowerUpText, &mut Text, &mut Node)>,
+) {
+    let Ok(power_ups) = power_ups_query.get_single() else {
+        return;
+    };
+
+    for (power_up, mut text, mut node) in text_query.iter_mut() {
+        let remaining = power_ups.remaining(power_up.0);
+        let display = if remaining.is_some() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
+        if let Some(remaining) = remaining {
+            set_text(
+                &mut text,
+                format!("{} {}s", power_up.0.label(), remaining.ceil() as u32),
+            );
+        }
+    }
+}
+
+/// The bar fills as the next charge comes back, and stays full when none are spent.
+fn update_dash(
+    dash_query: Query<&Dash, (With<Player>, Changed<Dash>)>,
+    mut fill_query: Query<&mut Node, With<DashBarFill>>,
+    mut text_query: Query<&mut Text, With<DashText>>,
+) {
+    let Ok(dash) = dash_query.get_single() else {
+        return;
+    };
+
+    if let Ok(mut fill) = fill_query.get_single_mut() {
+        let fraction = if dash.charges >= dash.max_charges {
+            1.0
+        } else {
+            dash.cooldown.fraction()
+        };
+        set_fill(&mut fill, fraction);
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        set_text(
+            &mut text,
+            format!("Dash {}/{}", dash.charges, dash.max_charges),
+        );
+    }
+}
+
+fn update_gun_cooldown(
+    gun_query: Query<&GunTimer, Changed<GunTimer>>,
+    player_query: Query<&PowerUps, With<Player>>,
+    tuning: Res<Tuning>,
+    mut fill_query: Query<&mut Node, With<CooldownBarFill>>,
+) {
+    let (Ok(gun_timer), Ok(mut fill)) = (gun_query.get_single(), fill_query.get_single_mut())
+    else {
+        return;
+    };
+
+    let interval = tuning.fire_interval
+        * player_query
+            .get_single()
+            .map_or(1.0, PowerUps::fire_interval_multiplier);
+    set_fill(&mut fill, gun_timer.0.elapsed_secs() / interval);
+}
+
+type AmmoReadout = (Option<&'static Ammo>, Has<Reloading>);
+/// Magazine and reserve if the gun has ammo, and whether it is reloading.
+type AmmoState = (Option<(u32, Option<u32>)>, bool);
+
+fn update_ammo_text(
+    gun_query: Query<AmmoReadout, With<Gun>>,
+    mut shown: Local<Option<AmmoState>>,
+    mut text_query: Query<&mut Text, With<AmmoText>>,
+) {
+    let (Ok((ammo, reloading)), Ok(mut text)) =
+        (gun_query.get_single(), text_query.get_single_mut())
+    else {
+        return;
+    };
+
+    let state = (ammo.map(|ammo| (ammo.magazine, ammo.reserve)), reloading);
+    if *shown == Some(state) {
+        return;
+    }
+    *shown = Some(state);
+    let value = match state {
+        (None, _) => "Ammo inf".to_string(),
+        (Some(_), true) => "Reloading".to_string(),
+        (Some((magazine, Some(reserve))), false) => format!("Ammo {magazine} / {reserve}"),
+        (Some((magazine, None)), false) => format!("Ammo {magazine} / inf"),
+    };
+    set_text(&mut text, value);
+}
+
+fn update_wave_text(
+    wave: Res<Wave>,
+    mut shown: Local<Option<(u32, u32)>>,
+    mut text_query: Query<&mut Text, With<WaveText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    // The timer ticks every frame, the text only changes once a second
+    let remaining = wave.timer.remaining_secs().ceil() as u32;
+    if *shown == Some((wave.number, remaining)) {
+        return;
+    }
+    *shown = Some((wave.number, remaining));
+    set_text(
+        &mut text,
+        format!(
+            "Wave {}  {}:{:02}",
+            wave.number,
+            remaining / 60,
+            remaining % 60
+        ),
+    );
+}
+
+fn update_score_text(stats: Res<RunStats>, mut text_query: Query<&mut Text, With<ScoreText>>) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    set_text(
+        &mut text,
+        format!("Score {}   Kills {}", stats.score, stats.kills),
+    );
+}
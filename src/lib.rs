@@ -1,15 +1,36 @@
 pub mod animation;
+pub mod audio;
+pub mod biome;
 pub mod camera;
 pub mod collision;
+pub mod console;
 pub mod constants;
+pub mod damage_numbers;
+pub mod dash;
+pub mod death;
+#[cfg(all(feature = "debug_overlay", debug_assertions))]
+pub mod debug_overlay;
 pub mod enemy;
-pub mod gui;
+pub mod events;
 pub mod gun;
+pub mod hud;
+pub mod lod;
+pub mod menu;
+pub mod minimap;
+pub mod particles;
+#[cfg(feature = "physics")]
+pub mod physics;
+pub mod pickups;
 pub mod player;
+pub mod pool;
+pub mod progression;
 pub mod resources;
+pub mod schedule;
+pub mod settings;
 pub mod state;
 pub mod world;
 
 pub use constants::*;
 pub use resources::*;
+pub use schedule::*;
 pub use state::*;
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use bevy::{math::vec3, prelude::*, time::common_conditions::on_timer};
+
+use crate::{
+    enemy::{get_random_position_in_ring, Enemy, LiveEnemy},
+    player::Player,
+    *,
+};
+
+pub struct LodPlugin;
+
+/// How much per-frame work an enemy gets, based on where it is relative to the camera.
+#[derive(Component, Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum EnemyLod {
+    /// Inside the camera view: moves, animates and flips every frame.
+    #[default]
+    OnScreen,
+    /// Off-screen but close: moves every frame, no animation.
+    Near,
+    /// Far off-screen: moves in coarse steps every few frames, no animation.
+    Far,
+}
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (update_enemy_lod, recycle_leashed_enemies)
+                .in_set(GameSet::Movement)
+                .run_if(on_timer(Duration::from_secs_f32(LOD_UPDATE_INTERVAL)))
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+impl EnemyLod {
+    pub fn is_visible(&self) -> bool {
+        *self == EnemyLod::OnScreen
+    }
+
+    /// Whether an enemy at this LOD moves on `frame`, and by how many frames' worth.
+    /// `Far` enemies are staggered by entity index so they don't all step on the same frame.
+    pub fn movement_step(&self, entity: Entity, frame: u32) -> Option<f32> {
+        match self {
+            EnemyLod::OnScreen | EnemyLod::Near => Some(1.0),
+            EnemyLod::Far => entity
+                .index()
+                .wrapping_add(frame)
+                .is_multiple_of(LOD_FAR_UPDATE_INTERVAL)
+                .then_some(LOD_FAR_UPDATE_INTERVAL as f32),
+        }
+    }
+}
+
+fn update_enemy_lod(
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    mut enemy_query: Query<(&Transform, &mut EnemyLod), With<Enemy>>,
+) {
+    if camera_query.is_empty() || enemy_query.is_empty() {
+        return;
+    }
+
+    let (camera_transform, projection) = camera_query.single();
+    let camera_pos = camera_transform.translation.truncate();
+    let view = Rect::from_corners(
+        projection.area.min + camera_pos,
+        projection.area.max + camera_pos,
+    )
+    .inflate(LOD_VIEW_MARGIN);
+
+    for (transform, mut lod) in enemy_query.iter_mut() {
+        let pos = transform.translation.truncate();
+        let new_lod = if view.contains(pos) {
+            EnemyLod::OnScreen
+        } else if pos.distance_squared(camera_pos) <= LOD_NEAR_DISTANCE * LOD_NEAR_DISTANCE {
+            EnemyLod::Near
+        } else {
+            EnemyLod::Far
+        };
+
+        // Avoid tripping change detection when nothing changed
+        lod.set_if_neq(new_lod);
+    }
+}
+
+fn recycle_leashed_enemies(
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<(&mut Transform, &mut EnemyLod), (LiveEnemy, Without<Player>)>,
+) {
+    if player_query.is_empty() || enemy_query.is_empty() {
+        return;
+    }
+
+    let player_pos = player_query.single().translation.truncate();
+    for (mut transform, mut lod) in enemy_query.iter_mut() {
+        let pos = transform.translation.truncate();
+        if pos.distance_squared(player_pos) <= ENEMY_LEASH_DISTANCE * ENEMY_LEASH_DISTANCE {
+            continue;
+        }
+
+        let (x, y) = get_random_position_in_ring(
+            player_pos,
+            ENEMY_RECYCLE_MIN_DISTANCE..ENEMY_RECYCLE_MAX_DISTANCE,
+        );
+        transform.translation = vec3(x, y, transform.translation.z);
+        *lod = EnemyLod::Near;
+    }
+}
@@ -1,45 +1,79 @@
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 use z_attack_game::animation::AnimationsPlugin;
+use z_attack_game::audio::GameAudioPlugin;
+use z_attack_game::biome::BiomePlugin;
 use z_attack_game::camera::CameraPlugin;
 use z_attack_game::collision::CollisionPlugin;
+use z_attack_game::console::ConsolePlugin;
+use z_attack_game::damage_numbers::DamageNumbersPlugin;
+use z_attack_game::dash::DashPlugin;
+use z_attack_game::death::DeathPlugin;
+#[cfg(all(feature = "debug_overlay", debug_assertions))]
+use z_attack_game::debug_overlay::DebugOverlayPlugin;
 use z_attack_game::enemy::EnemyPlugin;
-use z_attack_game::gui::GUIPlugin;
+use z_attack_game::events::GameEventsPlugin;
 use z_attack_game::gun::GunPlugin;
+use z_attack_game::hud::HudPlugin;
+use z_attack_game::lod::LodPlugin;
+use z_attack_game::menu::MenuPlugin;
+use z_attack_game::minimap::MinimapPlugin;
+use z_attack_game::particles::ParticlePlugin;
+use z_attack_game::pickups::PickupPlugin;
 use z_attack_game::player::PlayerPlugin;
+use z_attack_game::pool::PoolPlugin;
+use z_attack_game::progression::ProgressionPlugin;
+use z_attack_game::settings::{Settings, SettingsPlugin};
 use z_attack_game::world::WorldPlugin;
 use z_attack_game::*;
 
 fn main() {
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        resizable: true,
-                        focused: true,
-                        resolution: (WW, WH).into(),
-                        ..default()
-                    }),
-                    ..default()
-                }),
-        )
-        .init_state::<GameState>()
-        .add_plugins(LogDiagnosticsPlugin::default())
-        .add_plugins(FrameTimeDiagnosticsPlugin)
-        .insert_resource(ClearColor(Color::srgb_u8(
-            BG_COLOR.0, BG_COLOR.1, BG_COLOR.2,
-        )))
-        //plugins
-        .add_plugins(CameraPlugin)
-        .add_plugins(PlayerPlugin)
-        .add_plugins(AnimationsPlugin)
-        .add_plugins(ResourcesPlugin)
-        .add_plugins(GunPlugin)
-        .add_plugins(WorldPlugin)
-        .add_plugins(EnemyPlugin)
-        .add_plugins(CollisionPlugin)
-        .add_plugins(GUIPlugin)
-        .run();
+    let settings = Settings::load();
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(WindowPlugin {
+                primary_window: Some(settings.window()),
+                ..default()
+            }),
+    )
+    .insert_resource(settings)
+    .init_state::<GameState>()
+    .add_plugins(LogDiagnosticsPlugin::default())
+    .add_plugins(FrameTimeDiagnosticsPlugin)
+    .insert_resource(ClearColor(Color::srgb_u8(
+        BG_COLOR.0, BG_COLOR.1, BG_COLOR.2,
+    )))
+    //plugins
+    .add_plugins(SchedulePlugin)
+    .add_plugins(SettingsPlugin)
+    .add_plugins(ConsolePlugin)
+    .add_plugins(GameEventsPlugin)
+    .add_plugins(CameraPlugin)
+    .add_plugins(PlayerPlugin)
+    .add_plugins(DashPlugin)
+    .add_plugins(AnimationsPlugin)
+    .add_plugins(ResourcesPlugin)
+    .add_plugins(PoolPlugin)
+    .add_plugins(GunPlugin)
+    .add_plugins(BiomePlugin)
+    .add_plugins(WorldPlugin)
+    .add_plugins(ProgressionPlugin)
+    .add_plugins(PickupPlugin)
+    .add_plugins(EnemyPlugin)
+    .add_plugins(DeathPlugin)
+    .add_plugins(ParticlePlugin)
+    .add_plugins(DamageNumbersPlugin)
+    .add_plugins(LodPlugin)
+    .add_plugins(CollisionPlugin)
+    .add_plugins(HudPlugin)
+    .add_plugins(MenuPlugin)
+    .add_plugins(MinimapPlugin)
+    .add_plugins(GameAudioPlugin);
+
+    #[cfg(all(feature = "debug_overlay", debug_assertions))]
+    app.add_plugins(DebugOverlayPlugin);
+
+    app.run();
 }
@@ -0,0 +1,510 @@
+//! Main menu, pause menu and the settings screen reachable from both.
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    audio::{PlaySfx, Sfx},
+    settings::{is_bindable, key_name, Action, DisplayMode, Settings},
+    *,
+};
+
+pub struct MenuPlugin;
+
+const BUTTON_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.45, 0.35, 0.15);
+
+/// Whether the settings screen is shown over the main or pause menu.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum SettingsScreen {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Play,
+    Resume,
+    OpenSettings,
+    CloseSettings,
+    Quit,
+    Step(SettingRow, i32),
+    Rebind(Action),
+}
+
+/// An option on the settings screen changed with `<` and `>` buttons.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum SettingRow {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    DisplayMode,
+    Resolution,
+    Vsync,
+    FpsCap,
+    ScreenShake,
+    DamageNumbers,
+}
+
+/// The text showing a setting's current value.
+#[derive(Component)]
+enum SettingValue {
+    Row(SettingRow),
+    Key(Action),
+}
+
+/// The action waiting for a key press to be bound to.
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+/// The main or pause menu, hidden while the settings screen is open.
+#[derive(Component)]
+struct MenuPanel;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<SettingsScreen>()
+            .enable_state_scoped_entities::<GameState>()
+            .enable_state_scoped_entities::<SettingsScreen>()
+            .init_resource::<Rebinding>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(GameState::Paused), (spawn_pause_menu, pause_time))
+            .add_systems(OnExit(GameState::Paused), (close_settings, resume_time))
+            .add_systems(OnEnter(SettingsScreen::Open), spawn_settings_screen)
+            .add_systems(OnExit(SettingsScreen::Open), save_settings)
+            .add_systems(
+                Update,
+                (
+                    handle_menu_keys.in_set(GameSet::Input),
+                    (
+                        show_menu_panels.run_if(state_changed::<SettingsScreen>),
+                        handle_menu_buttons,
+                        capture_rebind_key
+                            .run_if(|rebinding: Res<Rebinding>| rebinding.0.is_some()),
+                        update_setting_values.run_if(
+                            resource_changed::<Settings>
+                                .or(resource_changed::<Rebinding>)
+                                .or(state_changed::<SettingsScreen>),
+                        ),
+                        highlight_buttons,
+                    )
+                        .chain()
+                        .in_set(GameSet::Ui),
+                )
+                    .run_if(not(in_state(GameState::Loading))),
+            );
+    }
+}
+
+impl SettingRow {
+    const AUDIO: [SettingRow; 3] = [
+        SettingRow::MasterVolume,
+        SettingRow::MusicVolume,
+        SettingRow::SfxVolume,
+    ];
+    const VIDEO: [SettingRow; 4] = [
+        SettingRow::DisplayMode,
+        SettingRow::Resolution,
+        SettingRow::Vsync,
+        SettingRow::FpsCap,
+    ];
+    const ACCESSIBILITY: [SettingRow; 2] = [SettingRow::ScreenShake, SettingRow::DamageNumbers];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingRow::MasterVolume => "Master volume",
+            SettingRow::MusicVolume => "Music volume",
+            SettingRow::SfxVolume => "Effects volume",
+            SettingRow::DisplayMode => "Window mode",
+            SettingRow::Resolution => "Resolution",
+            SettingRow::Vsync => "VSync",
+            SettingRow::FpsCap => "FPS cap",
+            SettingRow::ScreenShake => "Screen shake",
+            SettingRow::DamageNumbers => "Damage numbers",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let percent = |value: f32| format!("{:.0}%", value * 100.0);
+        let on_off = |value: bool| if value { "On" } else { "Off" }.to_string();
+        match self {
+            SettingRow::MasterVolume => percent(settings.master_volume),
+            SettingRow::MusicVolume => percent(settings.music_volume),
+            SettingRow::SfxVolume => percent(settings.sfx_volume),
+            SettingRow::DisplayMode => settings.display_mode.name().to_string(),
+            SettingRow::Resolution => {
+                format!("{}x{}", settings.resolution.x, settings.resolution.y)
+            }
+            SettingRow::Vsync => on_off(settings.vsync),
+            SettingRow::FpsCap => match settings.fps_cap {
+                0 => "Off".to_string(),
+                cap => cap.to_string(),
+            },
+            SettingRow::ScreenShake => percent(settings.screen_shake),
+            SettingRow::DamageNumbers => on_off(settings.damage_numbers),
+        }
+    }
+
+    /// Moves the setting `step` notches, wrapping around for lists.
+    fn step(&self, settings: &mut Settings, step: i32) {
+        let notch = |value: f32| (value + step as f32 * 0.1).clamp(0.0, 1.0);
+        let cycle =
+            |index: usize, len: usize| (index as i32 + step).rem_euclid(len as i32) as usize;
+        match self {
+            SettingRow::MasterVolume => settings.master_volume = notch(settings.master_volume),
+            SettingRow::MusicVolume => settings.music_volume = notch(settings.music_volume),
+            SettingRow::SfxVolume => settings.sfx_volume = notch(settings.sfx_volume),
+            SettingRow::DisplayMode => {
+                let index = DisplayMode::ALL
+                    .iter()
+                    .position(|mode| *mode == settings.display_mode)
+                    .unwrap_or_default();
+                settings.display_mode = DisplayMode::ALL[cycle(index, DisplayMode::ALL.len())];
+            }
+            SettingRow::Resolution => {
+                // A size from the config file that isn't listed starts from the first one
+                let index = RESOLUTIONS
+                    .iter()
+                    .position(|(w, h)| UVec2::new(*w, *h) == settings.resolution)
+                    .map(|index| cycle(index, RESOLUTIONS.len()))
+                    .unwrap_or_default();
+                let (width, height) = RESOLUTIONS[index];
+                settings.resolution = UVec2::new(width, height);
+            }
+            SettingRow::Vsync => settings.vsync = !settings.vsync,
+            SettingRow::FpsCap => {
+                let index = FPS_CAPS
+                    .iter()
+                    .position(|cap| *cap == settings.fps_cap)
+                    .map(|index| cycle(index, FPS_CAPS.len()))
+                    .unwrap_or_default();
+                settings.fps_cap = FPS_CAPS[index];
+            }
+            SettingRow::ScreenShake => settings.screen_shake = notch(settings.screen_shake),
+            SettingRow::DamageNumbers => settings.damage_numbers = !settings.damage_numbers,
+        }
+    }
+}
+
+fn text_font(font: &GlobalFont, size: f32) -> TextFont {
+    TextFont {
+        font: font.0.clone(),
+        font_size: size,
+        ..default()
+    }
+}
+
+/// Full-screen dimmed backdrop with its contents centred in a column.
+fn menu_root() -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        GlobalZIndex(1),
+    )
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &GlobalFont,
+    width: f32,
+    label: impl Into<String>,
+    button: MenuButton,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(width),
+                padding: UiRect::all(Val::Px(6.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(BUTTON_COLOR),
+            button,
+        ))
+        .with_child((Text::new(label), text_font(font, MENU_FONT_SIZE)));
+}
+
+fn spawn_title_menu(
+    commands: &mut Commands,
+    font: &GlobalFont,
+    title: &str,
+    buttons: &[(&str, MenuButton)],
+    scope: GameState,
+) {
+    commands
+        .spawn((menu_root(), MenuPanel, StateScoped(scope)))
+        .with_children(|parent| {
+            parent.spawn((Text::new(title), text_font(font, MENU_TITLE_FONT_SIZE)));
+            for (label, button) in buttons {
+                spawn_button(parent, font, MENU_BUTTON_WIDTH, *label, *button);
+            }
+        });
+}
+
+fn spawn_main_menu(mut commands: Commands, font: Res<GlobalFont>) {
+    spawn_title_menu(
+        &mut commands,
+        &font,
+        "Z Attack",
+        &[
+            ("Play", MenuButton::Play),
+            ("Settings", MenuButton::OpenSettings),
+            ("Quit", MenuButton::Quit),
+        ],
+        GameState::MainMenu,
+    );
+}
+
+fn spawn_pause_menu(mut commands: Commands, font: Res<GlobalFont>) {
+    spawn_title_menu(
+        &mut commands,
+        &font,
+        "Paused",
+        &[
+            ("Resume", MenuButton::Resume),
+            ("Settings", MenuButton::OpenSettings),
+            ("Quit", MenuButton::Quit),
+        ],
+        GameState::Paused,
+    );
+}
+
+fn spawn_settings_screen(mut commands: Commands, font: Res<GlobalFont>) {
+    let header = |parent: &mut ChildBuilder, title: &str| {
+        parent.spawn((
+            Text::new(title),
+            text_font(&font, MENU_FONT_SIZE),
+            TextColor(Color::srgb(1.0, 0.85, 0.3)),
+            Node {
+                margin: UiRect::top(Val::Px(8.0)),
+                ..default()
+            },
+        ));
+    };
+    let row = |parent: &mut ChildBuilder, label: &str, contents: &dyn Fn(&mut ChildBuilder)| {
+        parent
+            .spawn(Node {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(label),
+                    text_font(&font, MENU_FONT_SIZE),
+                    Node {
+                        width: Val::Px(MENU_LABEL_WIDTH),
+                        ..default()
+                    },
+                ));
+                contents(parent);
+            });
+    };
+    let setting_rows = |parent: &mut ChildBuilder, title: &str, rows: &[SettingRow]| {
+        header(parent, title);
+        for setting in rows {
+            row(parent, setting.label(), &|parent| {
+                spawn_button(parent, &font, 40.0, "<", MenuButton::Step(*setting, -1));
+                parent.spawn((
+                    Text::default(),
+                    text_font(&font, MENU_FONT_SIZE),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Node {
+                        width: Val::Px(MENU_VALUE_WIDTH),
+                        ..default()
+                    },
+                    SettingValue::Row(*setting),
+                ));
+                spawn_button(parent, &font, 40.0, ">", MenuButton::Step(*setting, 1));
+            });
+        }
+    };
+
+    commands
+        .spawn((menu_root(), StateScoped(SettingsScreen::Open)))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings"),
+                text_font(&font, MENU_TITLE_FONT_SIZE),
+            ));
+            setting_rows(parent, "Audio", &SettingRow::AUDIO);
+            setting_rows(parent, "Video", &SettingRow::VIDEO);
+            header(parent, "Controls");
+            for action in Action::ALL {
+                row(parent, action.label(), &|parent| {
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(MENU_VALUE_WIDTH + 96.0),
+                                padding: UiRect::all(Val::Px(6.0)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            BackgroundColor(BUTTON_COLOR),
+                            MenuButton::Rebind(action),
+                        ))
+                        .with_child((
+                            Text::default(),
+                            text_font(&font, MENU_FONT_SIZE),
+                            SettingValue::Key(action),
+                        ));
+                });
+            }
+            setting_rows(parent, "Accessibility", &SettingRow::ACCESSIBILITY);
+            parent
+                .spawn(Node {
+                    margin: UiRect::top(Val::Px(12.0)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(
+                        parent,
+                        &font,
+                        MENU_BUTTON_WIDTH,
+                        "Back",
+                        MenuButton::CloseSettings,
+                    );
+                });
+        });
+}
+
+fn show_menu_panels(
+    settings_screen: Res<State<SettingsScreen>>,
+    mut panel_query: Query<&mut Node, With<MenuPanel>>,
+) {
+    for mut node in panel_query.iter_mut() {
+        node.display = match settings_screen.get() {
+            SettingsScreen::Open => Display::None,
+            SettingsScreen::Closed => Display::Flex,
+        };
+    }
+}
+
+/// Escape pauses and resumes the game, backs out of the settings screen and
+/// cancels a rebind.
+fn handle_menu_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    game_state: Res<State<GameState>>,
+    settings_screen: Res<State<SettingsScreen>>,
+    rebinding: Res<Rebinding>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_settings_screen: ResMut<NextState<SettingsScreen>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) || rebinding.0.is_some() {
+        return;
+    }
+
+    if *settings_screen.get() == SettingsScreen::Open {
+        next_settings_screen.set(SettingsScreen::Closed);
+        return;
+    }
+    match game_state.get() {
+        GameState::InGame => next_game_state.set(GameState::Paused),
+        GameState::Paused => next_game_state.set(GameState::InGame),
+        _ => {}
+    }
+}
+
+fn handle_menu_buttons(
+    button_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_settings_screen: ResMut<NextState<SettingsScreen>>,
+    mut sfx_events: EventWriter<PlaySfx>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    for (interaction, button) in button_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        sfx_events.send(Sfx::UiClick.global());
+        match button {
+            MenuButton::Play => next_game_state.set(GameState::GameInit),
+            MenuButton::Resume => next_game_state.set(GameState::InGame),
+            MenuButton::OpenSettings => next_settings_screen.set(SettingsScreen::Open),
+            MenuButton::CloseSettings => next_settings_screen.set(SettingsScreen::Closed),
+            MenuButton::Quit => {
+                exit_events.send(AppExit::Success);
+            }
+            MenuButton::Step(row, step) => row.step(&mut settings, *step),
+            MenuButton::Rebind(action) => rebinding.0 = Some(*action),
+        }
+    }
+}
+
+/// Binds the next key pressed to the action being rebound. Escape cancels.
+fn capture_rebind_key(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(key) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+
+    if *key == KeyCode::Escape {
+        rebinding.0 = None;
+    } else if is_bindable(*key) {
+        settings.keys.bind(action, *key);
+        rebinding.0 = None;
+    }
+}
+
+fn update_setting_values(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    mut value_query: Query<(&mut Text, &SettingValue)>,
+) {
+    for (mut text, value) in value_query.iter_mut() {
+        text.0 = match value {
+            SettingValue::Row(row) => row.value(&settings),
+            SettingValue::Key(action) if rebinding.0 == Some(*action) => "Press a key".to_string(),
+            SettingValue::Key(action) => key_name(settings.keys.get(*action)),
+        };
+    }
+}
+
+type ChangedButton = (With<Button>, Changed<Interaction>);
+
+fn highlight_buttons(mut button_query: Query<(&Interaction, &mut BackgroundColor), ChangedButton>) {
+    for (interaction, mut color) in button_query.iter_mut() {
+        color.0 = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+}
+
+fn close_settings(mut next_settings_screen: ResMut<NextState<SettingsScreen>>) {
+    next_settings_screen.set(SettingsScreen::Closed);
+}
+
+fn save_settings(settings: Res<Settings>) {
+    settings.save();
+}
+
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
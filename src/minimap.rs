@@ -0,0 +1,256 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use bevy::{
+    asset::RenderAssetUsages,
+    math::vec2,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    time::common_conditions::on_timer,
+};
+
+use crate::{console::ConsoleAppExt, enemy::LiveEnemy, player::Player, *};
+
+pub struct MinimapPlugin;
+
+/// Minimap settings, centred on the player.
+#[derive(Resource)]
+pub struct Minimap {
+    /// World units from the player to the edge of the map.
+    pub range: f32,
+    pub corner: MinimapCorner,
+    /// On-screen width and height in pixels.
+    pub size: f32,
+}
+
+/// Screen corner the minimap sits in, set with `set minimap_corner <corner>`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum MinimapCorner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Shows the entity on the minimap as a dot, e.g. pickups. Plain enemies are
+/// only counted into the heatmap. There are no bosses yet; when they land they
+/// need a marker too.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MinimapMarker(pub Color);
+
+#[derive(Component)]
+struct MinimapImage;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Minimap>()
+            .add_console_var("minimap_range", |minimap: &mut Minimap| &mut minimap.range)
+            .add_console_var("minimap_corner", |minimap: &mut Minimap| {
+                &mut minimap.corner
+            })
+            .add_systems(OnEnter(GameState::GameInit), spawn_minimap)
+            .add_systems(
+                Update,
+                (
+                    zoom_minimap.in_set(GameSet::Input),
+                    (
+                        place_minimap.run_if(resource_changed::<Minimap>),
+                        draw_minimap
+                            .run_if(on_timer(Duration::from_secs_f32(MINIMAP_UPDATE_INTERVAL))),
+                    )
+                        .in_set(GameSet::Ui),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            range: MINIMAP_DEFAULT_RANGE,
+            corner: MinimapCorner::default(),
+            size: MINIMAP_SIZE,
+        }
+    }
+}
+
+impl MinimapCorner {
+    pub const ALL: [MinimapCorner; 4] = [
+        MinimapCorner::TopLeft,
+        MinimapCorner::TopRight,
+        MinimapCorner::BottomLeft,
+        MinimapCorner::BottomRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinimapCorner::TopLeft => "top_left",
+            MinimapCorner::TopRight => "top_right",
+            MinimapCorner::BottomLeft => "bottom_left",
+            MinimapCorner::BottomRight => "bottom_right",
+        }
+    }
+}
+
+impl fmt::Display for MinimapCorner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for MinimapCorner {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|corner| corner.name() == name)
+            .ok_or(())
+    }
+}
+
+fn spawn_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = Image::new_fill(
+        Extent3d {
+            width: MINIMAP_RESOLUTION,
+            height: MINIMAP_RESOLUTION,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BorderColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ImageNode::new(images.add(image)),
+        MinimapImage,
+    ));
+}
+
+fn zoom_minimap(keyboard_input: Res<ButtonInput<KeyCode>>, mut minimap: ResMut<Minimap>) {
+    let zoom = if keyboard_input.just_pressed(KeyCode::Equal) {
+        0.5
+    } else if keyboard_input.just_pressed(KeyCode::Minus) {
+        2.0
+    } else {
+        return;
+    };
+
+    minimap.range = (minimap.range * zoom).clamp(MINIMAP_MIN_RANGE, MINIMAP_MAX_RANGE);
+}
+
+fn place_minimap(minimap: Res<Minimap>, mut node_query: Query<&mut Node, With<MinimapImage>>) {
+    let Ok(mut node) = node_query.get_single_mut() else {
+        return;
+    };
+
+    let margin = Val::Px(MINIMAP_MARGIN);
+    let (top, bottom) = match minimap.corner {
+        MinimapCorner::TopLeft | MinimapCorner::TopRight => (margin, Val::Auto),
+        MinimapCorner::BottomLeft | MinimapCorner::BottomRight => (Val::Auto, margin),
+    };
+    let (left, right) = match minimap.corner {
+        MinimapCorner::TopLeft | MinimapCorner::BottomLeft => (margin, Val::Auto),
+        MinimapCorner::TopRight | MinimapCorner::BottomRight => (Val::Auto, margin),
+    };
+    node.top = top;
+    node.bottom = bottom;
+    node.left = left;
+    node.right = right;
+    node.width = Val::Px(minimap.size);
+    node.height = Val::Px(minimap.size);
+}
+
+/// Redraws the minimap texture. Enemies are binned into texels and shaded by
+/// count, so the cost is one pass over positions no matter how many there are.
+fn draw_minimap(
+    minimap: Res<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    image_query: Query<&ImageNode, With<MinimapImage>>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<&Transform, LiveEnemy>,
+    marker_query: Query<(&Transform, &MinimapMarker)>,
+) {
+    let (Ok(image_node), Ok(player_transform)) =
+        (image_query.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let Some(image) = images.get_mut(&image_node.image) else {
+        return;
+    };
+
+    let resolution = MINIMAP_RESOLUTION as i32;
+    let center = player_transform.translation.truncate();
+    let texels_per_unit = resolution as f32 / (minimap.range * 2.0);
+    let to_texel = |pos: Vec2| -> IVec2 {
+        let offset = (pos - center) * texels_per_unit;
+        // Texture rows go down, world y goes up
+        IVec2::new(
+            (offset.x + resolution as f32 / 2.0).floor() as i32,
+            (resolution as f32 / 2.0 - offset.y).floor() as i32,
+        )
+    };
+    let in_map = |texel: IVec2| {
+        texel.cmpge(IVec2::ZERO).all() && texel.cmplt(IVec2::splat(resolution)).all()
+    };
+    let index = |texel: IVec2| (texel.y * resolution + texel.x) as usize;
+
+    let mut counts = vec![0u32; (resolution * resolution) as usize];
+    for transform in enemy_query.iter() {
+        let texel = to_texel(transform.translation.truncate());
+        if in_map(texel) {
+            counts[index(texel)] += 1;
+        }
+    }
+
+    let bounds_min = to_texel(vec2(-WORLD_W, WORLD_H));
+    let bounds_max = to_texel(vec2(WORLD_W, -WORLD_H));
+    let on_bounds = |texel: IVec2| {
+        let inside = texel.cmpge(bounds_min).all() && texel.cmple(bounds_max).all();
+        inside && (texel.cmpeq(bounds_min).any() || texel.cmpeq(bounds_max).any())
+    };
+
+    let mut pixels = vec![[0u8; 4]; counts.len()];
+    for (i, (pixel, count)) in pixels.iter_mut().zip(&counts).enumerate() {
+        let texel = IVec2::new(i as i32 % resolution, i as i32 / resolution);
+        *pixel = if on_bounds(texel) {
+            [200, 200, 200, 255]
+        } else if *count == 0 {
+            [10, 12, 10, 150]
+        } else {
+            let heat = (*count as f32 / MINIMAP_HEAT_SATURATION).min(1.0);
+            [
+                (120.0 + 135.0 * heat) as u8,
+                (60.0 * (1.0 - heat)) as u8,
+                20,
+                (160.0 + 95.0 * heat) as u8,
+            ]
+        };
+    }
+
+    let mut draw_dot = |texel: IVec2, color: [u8; 4]| {
+        for offset in [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE] {
+            if in_map(texel + offset) {
+                pixels[index(texel + offset)] = color;
+            }
+        }
+    };
+    for (transform, marker) in marker_query.iter() {
+        draw_dot(
+            to_texel(transform.translation.truncate()),
+            marker.0.to_srgba().to_u8_array(),
+        );
+    }
+    draw_dot(to_texel(center), [255, 255, 255, 255]);
+
+    image.data = pixels.into_iter().flatten().collect();
+}
@@ -0,0 +1,266 @@
+use std::{f32::consts::PI, ops::Range};
+
+use bevy::{math::vec2, prelude::*};
+use rand::Rng;
+
+use crate::{
+    enemy::Enemy,
+    events::{BulletHit, EnemyKilled},
+    pool::ParticlePool,
+    *,
+};
+
+pub struct ParticlePlugin;
+
+/// Sprite-based CPU particle emitter. Emits `burst` particles on its first
+/// frame, then `rate` per second until `duration` runs out, after which the
+/// emitter entity is despawned. Particles come from [`ParticlePool`] and are
+/// dropped once [`MAX_PARTICLES`] are alive.
+#[derive(Component, Clone)]
+#[require(EmitterState)]
+pub struct ParticleEmitter {
+    pub rate: f32,
+    pub burst: u32,
+    pub duration: f32,
+    pub lifetime: Range<f32>,
+    pub speed: Range<f32>,
+    /// Particles leave within `spread` radians either side of this direction.
+    pub direction: Vec2,
+    pub spread: f32,
+    pub gravity: Vec2,
+    /// Colour at birth and at death, interpolated over the particle's life.
+    pub color: (Color, Color),
+    /// Scale at birth and at death, interpolated over the particle's life.
+    pub scale: (f32, f32),
+}
+
+#[derive(Component, Default)]
+pub struct EmitterState {
+    elapsed: f32,
+    pending: f32,
+    burst_done: bool,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub gravity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+    pub color: (Color, Color),
+    pub scale: (f32, f32),
+}
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                spawn_hit_emitters,
+                spawn_death_emitters,
+                emit_particles,
+                update_particles,
+            )
+                .chain()
+                .in_set(GameSet::Animation)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 0.0,
+            burst: 0,
+            duration: 0.0,
+            lifetime: 0.5..0.5,
+            speed: 100.0..100.0,
+            direction: Vec2::X,
+            spread: PI,
+            gravity: Vec2::ZERO,
+            color: (Color::WHITE, Color::WHITE.with_alpha(0.0)),
+            scale: (1.0, 1.0),
+        }
+    }
+}
+
+impl ParticleEmitter {
+    pub fn muzzle_flash(direction: Vec2) -> Self {
+        Self {
+            burst: 6,
+            lifetime: 0.05..0.12,
+            speed: 150.0..350.0,
+            direction,
+            spread: 0.35,
+            color: (Color::srgb(1.0, 0.9, 0.4), Color::srgba(1.0, 0.4, 0.1, 0.0)),
+            scale: (2.0, 0.5),
+            ..default()
+        }
+    }
+
+    pub fn blood(direction: Vec2) -> Self {
+        Self {
+            burst: 8,
+            lifetime: 0.25..0.5,
+            speed: 80.0..220.0,
+            direction,
+            spread: 0.6,
+            gravity: vec2(0.0, -400.0),
+            color: (
+                Color::srgb(0.6, 0.05, 0.05),
+                Color::srgba(0.3, 0.0, 0.0, 0.0),
+            ),
+            scale: (1.5, 0.8),
+            ..default()
+        }
+    }
+
+    /// Bullets glancing off anything that isn't an enemy.
+    pub fn sparks(direction: Vec2) -> Self {
+        Self {
+            burst: 6,
+            lifetime: 0.1..0.25,
+            speed: 150.0..300.0,
+            direction: -direction,
+            spread: 0.9,
+            gravity: vec2(0.0, -200.0),
+            color: (Color::srgb(1.0, 1.0, 0.7), Color::srgba(1.0, 0.5, 0.1, 0.0)),
+            scale: (1.0, 0.3),
+            ..default()
+        }
+    }
+
+    pub fn gore() -> Self {
+        Self {
+            rate: 40.0,
+            burst: 20,
+            duration: 0.25,
+            lifetime: 0.3..0.7,
+            speed: 60.0..260.0,
+            direction: Vec2::Y,
+            spread: PI,
+            gravity: vec2(0.0, -500.0),
+            color: (
+                Color::srgb(0.55, 0.05, 0.05),
+                Color::srgba(0.25, 0.0, 0.0, 0.0),
+            ),
+            scale: (2.0, 1.0),
+        }
+    }
+
+    fn particle(&self, rng: &mut impl Rng) -> Particle {
+        let angle = self.direction.to_angle() + rng.random_range(-self.spread..=self.spread);
+        Particle {
+            velocity: Vec2::from_angle(angle) * random_in(rng, &self.speed),
+            gravity: self.gravity,
+            age: 0.0,
+            lifetime: random_in(rng, &self.lifetime),
+            color: self.color,
+            scale: self.scale,
+        }
+    }
+}
+
+fn random_in(rng: &mut impl Rng, range: &Range<f32>) -> f32 {
+    if range.is_empty() {
+        range.start
+    } else {
+        rng.random_range(range.clone())
+    }
+}
+
+fn spawn_hit_emitters(
+    mut commands: Commands,
+    mut hit_events: EventReader<BulletHit>,
+    enemy_query: Query<(), With<Enemy>>,
+) {
+    for hit in hit_events.read() {
+        let emitter = if enemy_query.contains(hit.target) {
+            ParticleEmitter::blood(hit.direction)
+        } else {
+            ParticleEmitter::sparks(hit.direction)
+        };
+        commands.spawn((
+            emitter,
+            Transform::from_translation(hit.position.extend(12.0)),
+        ));
+    }
+}
+
+fn spawn_death_emitters(mut commands: Commands, mut killed_events: EventReader<EnemyKilled>) {
+    for killed in killed_events.read() {
+        commands.spawn((
+            ParticleEmitter::gore(),
+            Transform::from_translation(killed.position.extend(12.0)),
+        ));
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_pool: ResMut<ParticlePool>,
+    particle_query: Query<(), With<Particle>>,
+    mut emitter_query: Query<(Entity, &ParticleEmitter, &mut EmitterState, &Transform)>,
+) {
+    let mut budget = MAX_PARTICLES.saturating_sub(particle_query.iter().len());
+    let mut rng = rand::rng();
+    for (entity, emitter, mut state, transform) in emitter_query.iter_mut() {
+        let mut count = 0;
+        if !state.burst_done {
+            state.burst_done = true;
+            count += emitter.burst as usize;
+        }
+        if state.elapsed < emitter.duration {
+            state.pending += emitter.rate * time.delta_secs();
+            count += state.pending as usize;
+            state.pending = state.pending.fract();
+        }
+        state.elapsed += time.delta_secs();
+
+        // Over the cap the particles are simply never born
+        let count = count.min(budget);
+        budget -= count;
+        for _ in 0..count {
+            let particle = emitter.particle(&mut rng);
+            particle_pool.acquire(
+                &mut commands,
+                (
+                    Sprite::from_color(particle.color.0, Vec2::splat(PARTICLE_SIZE)),
+                    Transform::from_translation(transform.translation)
+                        .with_scale(Vec3::splat(particle.scale.0)),
+                    particle,
+                ),
+            );
+        }
+
+        if state.elapsed >= emitter.duration {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particle_pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite) in particle_query.iter_mut() {
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            particle_pool.release(&mut commands, entity);
+            continue;
+        }
+
+        let gravity = particle.gravity;
+        particle.velocity += gravity * dt;
+        transform.translation += particle.velocity.extend(0.0) * dt;
+
+        let t = particle.age / particle.lifetime;
+        sprite.color = particle.color.0.mix(&particle.color.1, t);
+        transform.scale = Vec3::splat(particle.scale.0.lerp(particle.scale.1, t));
+    }
+}
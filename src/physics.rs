@@ -0,0 +1,185 @@
+//! Physics-engine collision backend, enabled with the `physics` cargo feature.
+//!
+//! Every [`Collider`] gets a matching avian2d body: enemies and the player are
+//! dynamic and push each other, obstacles are static. avian's contact events
+//! are forwarded as this crate's [`CollisionStarted`]/[`CollisionEnded`], so
+//! gameplay code is the same for both backends.
+//!
+//! Bullets get no body. `handle_bullet_hits` sweeps them with avian shape casts
+//! from their previous position through [`BulletSweep`], so the spatial grid
+//! isn't built at all with this backend.
+
+use avian2d::prelude as avian;
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    collision::{Collider, ColliderShape, CollisionEnded, CollisionLayers, CollisionPairs},
+    *,
+};
+
+pub struct PhysicsBackendPlugin;
+
+/// Stands in for [`SpatialGrid::sweep`](crate::collision::SpatialGrid::sweep)
+/// when bullets are swept with avian.
+#[derive(SystemParam)]
+pub struct BulletSweep<'w, 's> {
+    spatial_query: avian::SpatialQuery<'w, 's>,
+    collider_query: Query<'w, 's, &'static Collider>,
+}
+
+impl Plugin for PhysicsBackendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(
+            avian::PhysicsPlugins::default().with_length_unit(TILE_W as f32 * SPRITE_SCALE_FACTOR),
+        )
+        .insert_resource(avian::Gravity::ZERO)
+        .add_observer(add_physics_body)
+        .add_observer(remove_physics_body)
+        .add_systems(
+            Update,
+            (sync_collision_layers, forward_collision_events)
+                .in_set(GameSet::Collision)
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn add_physics_body(
+    trigger: Trigger<OnAdd, Collider>,
+    mut commands: Commands,
+    collider_query: Query<(&Collider, &Transform)>,
+) {
+    let Ok((collider, transform)) = collider_query.get(trigger.entity()) else {
+        return;
+    };
+    // Bullets are shape cast instead, see `BulletSweep`
+    if collider.layers.intersects(CollisionLayers::BULLET) {
+        return;
+    }
+
+    // avian scales colliders by the transform, ours are already in world units
+    let mut entity_commands = commands.entity(trigger.entity());
+    entity_commands.insert((
+        physics_shape(collider.shape, transform.scale.truncate()),
+        physics_layers(collider),
+        avian::LockedAxes::ROTATION_LOCKED,
+    ));
+
+    if collider.layers.intersects(CollisionLayers::PICKUP) {
+        entity_commands.insert((avian::RigidBody::Static, avian::Sensor));
+    } else if collider.layers.intersects(CollisionLayers::OBSTACLE) {
+        entity_commands.insert(avian::RigidBody::Static);
+    } else {
+        entity_commands.insert((
+            avian::RigidBody::Dynamic,
+            avian::LinearDamping(PHYSICS_LINEAR_DAMPING),
+        ));
+    }
+}
+
+fn physics_shape(shape: ColliderShape, scale: Vec2) -> avian::Collider {
+    match shape {
+        ColliderShape::Circle { radius } => avian::Collider::circle(radius / scale.x),
+        ColliderShape::Aabb { half_extents } => {
+            let size = half_extents * 2.0 / scale;
+            avian::Collider::rectangle(size.x, size.y)
+        }
+    }
+}
+
+fn physics_layers(collider: &Collider) -> avian::CollisionLayers {
+    // Enemies only report hits against the player and bullets, but physically they shove each other too
+    let mut mask = collider.mask;
+    if collider.layers.intersects(CollisionLayers::ENEMY) {
+        mask = mask | CollisionLayers::ENEMY;
+    }
+    avian::CollisionLayers::new(collider.layers.0, mask.0)
+}
+
+/// Gameplay may change a collider's layers later on, like the player dashing
+/// through enemies; the body follows.
+fn sync_collision_layers(
+    mut collider_query: Query<(&Collider, &mut avian::CollisionLayers), Changed<Collider>>,
+) {
+    for (collider, mut layers) in collider_query.iter_mut() {
+        layers.set_if_neq(physics_layers(collider));
+    }
+}
+
+fn remove_physics_body(trigger: Trigger<OnRemove, Collider>, mut commands: Commands) {
+    // Pooled entities lose their `Collider` when parked, take the body with it
+    commands.entity(trigger.entity()).remove::<(
+        avian::Collider,
+        avian::CollisionLayers,
+        avian::LockedAxes,
+        avian::RigidBody,
+        avian::Sensor,
+        avian::LinearDamping,
+    )>();
+}
+
+fn forward_collision_events(
+    collider_query: Query<&Collider>,
+    mut pairs: ResMut<CollisionPairs>,
+    mut physics_started: EventReader<avian::CollisionStarted>,
+    mut physics_ended: EventReader<avian::CollisionEnded>,
+    mut started_events: EventWriter<collision::CollisionStarted>,
+    mut ended_events: EventWriter<CollisionEnded>,
+) {
+    // Skip contacts that only exist physically, like enemies shoving each other
+    let reports = |a: Entity, b: Entity| match (collider_query.get(a), collider_query.get(b)) {
+        (Ok(a), Ok(b)) => a.interacts_with(b),
+        _ => false,
+    };
+
+    for avian::CollisionEnded(a, b) in physics_ended.read() {
+        if pairs.remove(*a, *b) {
+            let (a, b) = collision::ordered(*a, *b);
+            ended_events.send(CollisionEnded(a, b));
+        }
+    }
+
+    for avian::CollisionStarted(a, b) in physics_started.read() {
+        if reports(*a, *b) && pairs.insert(*a, *b) {
+            let (a, b) = collision::ordered(*a, *b);
+            started_events.send(collision::CollisionStarted(a, b));
+        }
+    }
+}
+
+impl BulletSweep<'_, '_> {
+    /// First collider `collider` touches moving from `start` to `end`, with the
+    /// fraction of the way it got. Only hits pairs that would collide on the grid.
+    pub fn sweep(
+        &self,
+        entity: Entity,
+        collider: &Collider,
+        start: Vec2,
+        end: Vec2,
+    ) -> Option<(Entity, f32)> {
+        let delta = end - start;
+        let length = delta.length();
+        let filter =
+            avian::SpatialQueryFilter::from_mask(collider.mask.0).with_excluded_entities([entity]);
+        let hit = self.spatial_query.cast_shape_predicate(
+            &physics_shape(collider.shape, Vec2::ONE),
+            start,
+            0.0,
+            Dir2::new(delta).unwrap_or(Dir2::X),
+            &avian::ShapeCastConfig::from_max_distance(length),
+            &filter,
+            &|other| {
+                self.collider_query
+                    .get(other)
+                    .is_ok_and(|other| collider.interacts_with(other))
+            },
+        )?;
+
+        let t = if length > 0.0 {
+            hit.distance / length
+        } else {
+            0.0
+        };
+        Some((hit.entity, t))
+    }
+}
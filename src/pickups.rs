@@ -0,0 +1,423 @@
+//! Pickups dropped by enemies or spawned around the player, the XP gems every
+//! kill leaves behind, and the timed power-ups some pickups grant.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::common_conditions::on_timer, utils::HashMap};
+use rand::Rng;
+
+use crate::{
+    camera::CameraShake,
+    collision::{Collider, CollisionLayers, CollisionStarted},
+    death::Dying,
+    enemy::{get_random_position_in_ring, Enemy},
+    events::{EnemyKilled, PickupCollected, PlayerLeveledUp},
+    gun::{Ammo, Gun},
+    minimap::MinimapMarker,
+    player::{Health, Player, PlayerMovement},
+    progression::Experience,
+    *,
+};
+
+pub struct PickupPlugin;
+
+const XP_GEM_COLOR: Color = Color::srgb(0.3, 0.9, 1.0);
+
+/// Collected by touching it; expires after its lifetime, blinking towards the end.
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    pub lifetime: Timer,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum PickupKind {
+    Health,
+    /// Rounds for the gun's reserve, see [`AMMO_PACK_ROUNDS`].
+    Ammo,
+    /// Pulls every XP gem on the map to the player.
+    Magnet,
+    /// Kills every enemy within [`BOMB_RADIUS`].
+    Bomb,
+    PowerUp(PowerUp),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum PowerUp {
+    DoubleDamage,
+    RapidFire,
+    Speed,
+}
+
+/// Timed power-ups on the player, with the seconds each has left.
+///
+/// Picking up a power-up that is already active adds its duration to the time
+/// left, up to [`POWER_UP_MAX_SECS`]; the effect itself never doubles up.
+/// Different power-ups run side by side and their effects combine.
+#[derive(Component, Default)]
+pub struct PowerUps(HashMap<PowerUp, f32>);
+
+/// Experience left by a kill, collected by walking near it.
+#[derive(Component)]
+pub struct XpGem {
+    pub xp: u32,
+}
+
+/// A gem flying to the player, at `speed` world units per second.
+#[derive(Component, Default)]
+pub struct Magnetized {
+    speed: f32,
+}
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                move_gems.in_set(GameSet::Movement).after(PlayerMovement),
+                (collect_pickups, tick_power_ups).in_set(GameSet::Damage),
+                (
+                    (drop_kill_loot, collect_gems).chain(),
+                    spawn_world_pickups.run_if(on_timer(Duration::from_secs_f32(
+                        PICKUP_WORLD_SPAWN_INTERVAL,
+                    ))),
+                )
+                    .in_set(GameSet::Cleanup),
+                expire_pickups.in_set(GameSet::Animation),
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+impl PickupKind {
+    /// Picks a kind by drop weight: mostly health, bombs are rare.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        const WEIGHTS: [(PickupKind, u32); 7] = [
+            (PickupKind::Health, 30),
+            (PickupKind::Ammo, 25),
+            (PickupKind::Magnet, 10),
+            (PickupKind::Bomb, 5),
+            (PickupKind::PowerUp(PowerUp::DoubleDamage), 15),
+            (PickupKind::PowerUp(PowerUp::RapidFire), 15),
+            (PickupKind::PowerUp(PowerUp::Speed), 15),
+        ];
+
+        let total: u32 = WEIGHTS.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.random_range(0..total);
+        for (kind, weight) in WEIGHTS {
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        PickupKind::Health
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PickupKind::Health => Color::srgb(0.9, 0.15, 0.2),
+            PickupKind::Ammo => Color::srgb(0.8, 0.6, 0.2),
+            PickupKind::Magnet => Color::srgb(0.3, 0.5, 1.0),
+            PickupKind::Bomb => Color::srgb(0.15, 0.15, 0.15),
+            PickupKind::PowerUp(power_up) => power_up.color(),
+        }
+    }
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 3] = [PowerUp::DoubleDamage, PowerUp::RapidFire, PowerUp::Speed];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUp::DoubleDamage => "Double damage",
+            PowerUp::RapidFire => "Rapid fire",
+            PowerUp::Speed => "Speed",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PowerUp::DoubleDamage => Color::srgb(1.0, 0.4, 0.1),
+            PowerUp::RapidFire => Color::srgb(1.0, 0.9, 0.2),
+            PowerUp::Speed => Color::srgb(0.3, 0.9, 0.4),
+        }
+    }
+}
+
+impl PowerUps {
+    pub fn add(&mut self, power_up: PowerUp) {
+        let remaining = self.0.entry(power_up).or_default();
+        *remaining = (*remaining + POWER_UP_DURATION_SECS).min(POWER_UP_MAX_SECS);
+    }
+
+    /// Seconds left on `power_up`, if it is active.
+    pub fn remaining(&self, power_up: PowerUp) -> Option<f32> {
+        self.0.get(&power_up).copied()
+    }
+
+    pub fn is_active(&self, power_up: PowerUp) -> bool {
+        self.0.contains_key(&power_up)
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        if self.is_active(PowerUp::DoubleDamage) {
+            POWER_UP_DAMAGE_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+
+    pub fn fire_interval_multiplier(&self) -> f32 {
+        if self.is_active(PowerUp::RapidFire) {
+            POWER_UP_FIRE_INTERVAL_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_active(PowerUp::Speed) {
+            POWER_UP_SPEED_MULTIPLIER
+        } else {
+            1.0
+        }
+    }
+}
+
+pub fn spawn_pickup(commands: &mut Commands, kind: PickupKind, pos: Vec2) {
+    commands.spawn((
+        Sprite::from_color(kind.color(), Vec2::splat(PICKUP_SIZE)),
+        Transform::from_translation(pos.extend(2.0)),
+        Pickup {
+            kind,
+            lifetime: Timer::from_seconds(PICKUP_LIFE_TIME_IN_SECS, TimerMode::Once),
+        },
+        Collider::circle(PICKUP_COLLIDER_RADIUS)
+            .with_layers(CollisionLayers::PICKUP, CollisionLayers::PLAYER),
+        MinimapMarker(kind.color()),
+    ));
+}
+
+/// Every kill leaves a gem, and now and then a pickup. Past [`MAX_XP_GEMS`]
+/// the experience goes straight to the player instead.
+fn drop_kill_loot(
+    mut commands: Commands,
+    mut killed_events: EventReader<EnemyKilled>,
+    gem_query: Query<(), With<XpGem>>,
+    mut player_query: Query<&mut Experience, With<Player>>,
+    mut level_events: EventWriter<PlayerLeveledUp>,
+) {
+    let mut rng = rand::rng();
+    let mut gems = gem_query.iter().len();
+    let mut overflow = 0;
+    for killed in killed_events.read() {
+        if rng.random::<f32>() < PICKUP_DROP_CHANCE {
+            spawn_pickup(&mut commands, PickupKind::random(&mut rng), killed.position);
+        }
+
+        if gems >= MAX_XP_GEMS {
+            overflow += killed.kind.xp();
+            continue;
+        }
+        gems += 1;
+        commands.spawn((
+            Sprite::from_color(XP_GEM_COLOR, Vec2::splat(XP_GEM_SIZE)),
+            Transform::from_translation(killed.position.extend(1.5))
+                .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            XpGem {
+                xp: killed.kind.xp(),
+            },
+        ));
+    }
+
+    if let Ok(mut experience) = player_query.get_single_mut() {
+        gain_experience(&mut experience, overflow, &mut level_events);
+    }
+}
+
+fn gain_experience(
+    experience: &mut Experience,
+    xp: u32,
+    level_events: &mut EventWriter<PlayerLeveledUp>,
+) {
+    if xp > 0 && experience.gain(xp) > 0 {
+        level_events.send(PlayerLeveledUp {
+            level: experience.level,
+        });
+    }
+}
+
+fn spawn_world_pickups(mut commands: Commands, player_query: Query<&Transform, With<Player>>) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+
+    let (x, y) = get_random_position_in_ring(
+        transform.translation.truncate(),
+        PICKUP_WORLD_SPAWN_MIN_DISTANCE..PICKUP_WORLD_SPAWN_MAX_DISTANCE,
+    );
+    spawn_pickup(
+        &mut commands,
+        PickupKind::random(&mut rand::rng()),
+        Vec2::new(x, y),
+    );
+}
+
+type GemFilter = (With<XpGem>, Without<Player>);
+
+/// Gems near the player start flying to it and speed up until they arrive.
+fn move_gems(
+    mut commands: Commands,
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut gem_query: Query<(Entity, &mut Transform, Option<&mut Magnetized>), GemFilter>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let dt = time.delta_secs();
+    for (entity, mut transform, magnetized) in gem_query.iter_mut() {
+        let offset = player_pos - transform.translation.truncate();
+        let Some(mut magnetized) = magnetized else {
+            if offset.length_squared() < XP_GEM_ATTRACT_RADIUS * XP_GEM_ATTRACT_RADIUS {
+                commands.entity(entity).insert(Magnetized::default());
+            }
+            continue;
+        };
+
+        magnetized.speed =
+            (magnetized.speed + XP_GEM_MAGNET_ACCELERATION * dt).min(XP_GEM_MAGNET_MAX_SPEED);
+        let step = (magnetized.speed * dt).min(offset.length());
+        transform.translation += (offset.normalize_or_zero() * step).extend(0.0);
+    }
+}
+
+fn collect_gems(
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut Experience), With<Player>>,
+    gem_query: Query<(Entity, &Transform, &XpGem)>,
+    mut level_events: EventWriter<PlayerLeveledUp>,
+) {
+    let Ok((player_transform, mut experience)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let mut xp = 0;
+    for (entity, transform, gem) in gem_query.iter() {
+        if transform
+            .translation
+            .truncate()
+            .distance_squared(player_pos)
+            < XP_GEM_COLLECT_RADIUS * XP_GEM_COLLECT_RADIUS
+        {
+            xp += gem.xp;
+            commands.entity(entity).despawn();
+        }
+    }
+    gain_experience(&mut experience, xp, &mut level_events);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn collect_pickups(
+    mut commands: Commands,
+    mut started_events: EventReader<CollisionStarted>,
+    mut player_query: Query<(Entity, &Transform, &mut Health, &mut PowerUps), With<Player>>,
+    pickup_query: Query<(&Pickup, &Transform)>,
+    gem_query: Query<Entity, (With<XpGem>, Without<Magnetized>)>,
+    mut enemy_query: Query<(&mut Enemy, &Transform), Without<Dying>>,
+    mut gun_query: Query<&mut Ammo, With<Gun>>,
+    mut camera_shake: ResMut<CameraShake>,
+    mut collected_events: EventWriter<PickupCollected>,
+) {
+    let Ok((player, player_transform, mut health, mut power_ups)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    for CollisionStarted(a, b) in started_events.read() {
+        let Some((entity, (pickup, transform))) = [*a, *b]
+            .into_iter()
+            .find_map(|entity| pickup_query.get(entity).ok().map(|pickup| (entity, pickup)))
+        else {
+            continue;
+        };
+
+        match pickup.kind {
+            PickupKind::Health => {
+                health.current = (health.current + HEALTH_PACK_AMOUNT).min(health.max);
+            }
+            PickupKind::Ammo => {
+                if let Ok(mut ammo) = gun_query.get_single_mut() {
+                    ammo.add_reserve(AMMO_PACK_ROUNDS);
+                }
+            }
+            PickupKind::Magnet => {
+                for gem in gem_query.iter() {
+                    commands.entity(gem).insert(Magnetized::default());
+                }
+            }
+            PickupKind::Bomb => {
+                // Kills still count and drop loot through the usual death path
+                for (mut enemy, transform) in enemy_query.iter_mut() {
+                    if transform
+                        .translation
+                        .truncate()
+                        .distance_squared(player_pos)
+                        < BOMB_RADIUS * BOMB_RADIUS
+                    {
+                        enemy.health = 0.0;
+                        enemy.last_hit_by = Some(player);
+                    }
+                }
+                camera_shake.add_trauma(BOMB_TRAUMA);
+            }
+            PickupKind::PowerUp(power_up) => power_ups.add(power_up),
+        }
+
+        collected_events.send(PickupCollected {
+            kind: pickup.kind,
+            position: transform.translation.truncate(),
+        });
+        commands.entity(entity).despawn();
+    }
+}
+
+fn tick_power_ups(time: Res<Time>, mut power_ups_query: Query<&mut PowerUps>) {
+    let dt = time.delta_secs();
+    for mut power_ups in power_ups_query.iter_mut() {
+        if power_ups.0.is_empty() {
+            continue;
+        }
+        power_ups.0.retain(|_, remaining| {
+            *remaining -= dt;
+            *remaining > 0.0
+        });
+    }
+}
+
+/// Counts pickups down, blinking through the last [`PICKUP_BLINK_SECS`].
+fn expire_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickup_query: Query<(Entity, &mut Pickup, &mut Visibility)>,
+) {
+    for (entity, mut pickup, mut visibility) in pickup_query.iter_mut() {
+        if pickup.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let remaining = pickup.lifetime.remaining_secs();
+        let shown = remaining > PICKUP_BLINK_SECS || (remaining * PICKUP_BLINK_RATE).fract() < 0.5;
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
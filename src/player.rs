@@ -1,11 +1,43 @@
-use crate::*;
-use bevy::{math::vec3, prelude::*};
+use crate::{
+    biome::BiomeMap,
+    collision::{CollisionEnded, CollisionStarted},
+    console::{ConsoleAppExt, ConsoleArgs, ConsoleResult},
+    dash::WalkingPlayer,
+    enemy::Enemy,
+    events::{PlayerDamaged, PlayerDied},
+    pickups::PowerUps,
+    settings::{Keybindings, Settings},
+    *,
+};
+use bevy::{ecs::entity::EntityHashSet, math::vec3, prelude::*};
 
 pub struct PlayerPlugin;
 
 #[derive(Component)]
 pub struct Player;
 
+/// Systems that move the player. Movement that follows the player, like the
+/// gun or chasing enemies, runs after this within [`GameSet::Movement`].
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct PlayerMovement;
+
+#[derive(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+/// Ignores all incoming damage, toggled with the `god` console command.
+#[derive(Component)]
+pub struct GodMode;
+
+/// Ignores incoming damage until the timer runs out.
+#[derive(Component, Deref, DerefMut)]
+pub struct Invulnerable(pub Timer);
+
+/// Query filter for the player while damage can land.
+type Vulnerable = (With<Player>, Without<Invulnerable>, Without<GodMode>);
+
 #[derive(Component, Default)]
 pub enum PlayerState {
     #[default]
@@ -15,27 +47,34 @@ pub enum PlayerState {
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            handle_player_input.run_if(in_state(GameState::InGame)),
-        );
+        app.add_console_command("god", "god", &[], god_command)
+            .add_systems(
+                Update,
+                (
+                    handle_player_input
+                        .in_set(GameSet::Movement)
+                        .in_set(PlayerMovement),
+                    (
+                        tick_invulnerability,
+                        (handle_enemy_contact, apply_player_damage).chain(),
+                    )
+                        .in_set(GameSet::Damage),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
-fn handle_player_input(
-    mut query: Query<(&mut Transform, &mut PlayerState), With<Player>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-) {
-    if query.is_empty() {
-        return;
-    }
-
-    let (mut transform, mut player_state) = query.single_mut();
-    let w_key = keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp);
-    let s_key = keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown);
-    let a_key = keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft);
+/// Unit direction the movement keys point in, or zero when none are held or
+/// they cancel out.
+pub fn movement_input(keyboard_input: &ButtonInput<KeyCode>, keys: &Keybindings) -> Vec2 {
+    let w_key = keyboard_input.pressed(keys.move_up) || keyboard_input.pressed(KeyCode::ArrowUp);
+    let s_key =
+        keyboard_input.pressed(keys.move_down) || keyboard_input.pressed(KeyCode::ArrowDown);
+    let a_key =
+        keyboard_input.pressed(keys.move_left) || keyboard_input.pressed(KeyCode::ArrowLeft);
     let d_key =
-        keyboard_input.pressed(KeyCode::KeyD) || keyboard_input.pressed(KeyCode::ArrowRight);
+        keyboard_input.pressed(keys.move_right) || keyboard_input.pressed(KeyCode::ArrowRight);
 
     let mut delta = Vec2::ZERO;
 
@@ -52,13 +91,147 @@ fn handle_player_input(
         delta.x += 1.0;
     }
 
-    delta = delta.normalize();
+    delta.normalize_or_zero()
+}
+
+/// Dashing moves the player on its own, see `dash`.
+fn handle_player_input(
+    time: Res<Time>,
+    tuning: Res<Tuning>,
+    biome_map: Res<BiomeMap>,
+    mut query: Query<(&mut Transform, &mut PlayerState, &PowerUps), WalkingPlayer>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+) {
+    if query.is_empty() {
+        return;
+    }
+
+    let (mut transform, mut player_state, power_ups) = query.single_mut();
+    let delta = movement_input(&keyboard_input, &settings.keys);
 
-    if delta.is_finite() && (w_key || s_key || a_key || d_key) {
-        transform.translation += vec3(delta.x, delta.y, 0.0) * PLAYER_SPEED;
+    if delta != Vec2::ZERO {
+        let speed_multiplier = biome_map.speed_multiplier_at(transform.translation.truncate())
+            * power_ups.speed_multiplier();
+        transform.translation += vec3(delta.x, delta.y, 0.0)
+            * tuning.player_speed
+            * speed_multiplier
+            * time.delta_secs();
         transform.translation.z = 10.0;
         *player_state = PlayerState::Moving;
     } else {
         *player_state = PlayerState::Idle;
     }
 }
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in query.iter_mut() {
+        if invulnerable.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+/// Enemies touching the player hurt it every time its invulnerability runs out.
+#[allow(clippy::too_many_arguments)]
+fn handle_enemy_contact(
+    mut touching: Local<EntityHashSet>,
+    mut started_events: EventReader<CollisionStarted>,
+    mut ended_events: EventReader<CollisionEnded>,
+    player_query: Query<Entity, Vulnerable>,
+    all_players: Query<(), With<Player>>,
+    enemy_query: Query<(), With<Enemy>>,
+    tuning: Res<Tuning>,
+    mut damaged_events: EventWriter<PlayerDamaged>,
+) {
+    let enemy_of = |a: Entity, b: Entity| {
+        if all_players.contains(a) && enemy_query.contains(b) {
+            Some(b)
+        } else if all_players.contains(b) && enemy_query.contains(a) {
+            Some(a)
+        } else {
+            None
+        }
+    };
+
+    // Bullets end contacts with enemies too, only player pairs count here
+    for CollisionEnded(a, b) in ended_events.read() {
+        if let Some(enemy) = enemy_of(*a, *b) {
+            touching.remove(&enemy);
+        }
+    }
+    for CollisionStarted(a, b) in started_events.read() {
+        if let Some(enemy) = enemy_of(*a, *b) {
+            touching.insert(enemy);
+        }
+    }
+
+    // Pooled enemies leave without an ended event if they die mid-contact
+    touching.retain(|enemy| enemy_query.contains(*enemy));
+
+    if player_query.is_empty() {
+        return;
+    }
+    if let Some(enemy) = touching.iter().min() {
+        damaged_events.send(PlayerDamaged {
+            amount: tuning.enemy_contact_damage,
+            source: *enemy,
+        });
+    }
+}
+
+fn apply_player_damage(
+    mut commands: Commands,
+    mut damaged_events: EventReader<PlayerDamaged>,
+    mut player_query: Query<(Entity, &mut Health, &Transform), Vulnerable>,
+    mut died_events: EventWriter<PlayerDied>,
+) {
+    if player_query.is_empty() {
+        damaged_events.clear();
+        return;
+    }
+
+    let (entity, mut health, transform) = player_query.single_mut();
+    let Some(damage) = damaged_events
+        .read()
+        .map(|event| event.amount)
+        .reduce(f32::max)
+    else {
+        return;
+    };
+    if health.current <= 0.0 {
+        return;
+    }
+
+    health.current = (health.current - damage).max(0.0);
+    commands
+        .entity(entity)
+        .insert(Invulnerable(Timer::from_seconds(
+            PLAYER_INVULNERABILITY_SECS,
+            TimerMode::Once,
+        )));
+
+    if health.current <= 0.0 {
+        died_events.send(PlayerDied {
+            position: transform.translation.truncate(),
+        });
+    }
+}
+
+fn god_command(
+    In(_): In<ConsoleArgs>,
+    mut commands: Commands,
+    player_query: Query<(Entity, Has<GodMode>), With<Player>>,
+) -> ConsoleResult {
+    let (entity, god_mode) = player_query.get_single().map_err(|_| "no player")?;
+    if god_mode {
+        commands.entity(entity).remove::<GodMode>();
+    } else {
+        commands.entity(entity).insert(GodMode);
+    }
+    Ok(format!("god = {}", !god_mode))
+}
@@ -0,0 +1,155 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{ecs::entity::EntityHashSet, prelude::*, time::common_conditions::on_timer};
+
+use crate::{
+    animation::AnimationTimer,
+    collision::{Collider, PreviousPosition},
+    death::Dying,
+    enemy::{Enemy, HitFlash, Knockback},
+    gun::{Bullet, BulletDirection, Shooter, SpawnInstant},
+    particles::Particle,
+    GameSet, GameState, POOL_METRICS_LOG_INTERVAL,
+};
+
+pub struct PoolPlugin;
+
+/// Marker for an entity parked in a pool: hidden and stripped of its gameplay
+/// components until it is handed out again.
+#[derive(Component)]
+pub struct Pooled;
+
+/// Free list of parked entities. `B` is the bundle of gameplay components that
+/// is removed on release, so parked entities drop out of every gameplay query.
+#[derive(Resource)]
+pub struct Pool<B: Bundle> {
+    free: Vec<Entity>,
+    parked: EntityHashSet,
+    pub metrics: PoolMetrics,
+    marker: PhantomData<fn() -> B>,
+}
+
+pub type BulletPool = Pool<(
+    Bullet,
+    BulletDirection,
+    Shooter,
+    SpawnInstant,
+    PreviousPosition,
+    Collider,
+)>;
+pub type EnemyPool = Pool<(Enemy, Knockback, HitFlash, Dying, AnimationTimer, Collider)>;
+pub type ParticlePool = Pool<Particle>;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PoolMetrics {
+    /// Acquires served from the free list.
+    pub hits: u64,
+    /// Acquires that had to spawn a fresh entity.
+    pub misses: u64,
+    pub releases: u64,
+    pub peak_free: usize,
+}
+
+impl Plugin for PoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BulletPool::default())
+            .insert_resource(EnemyPool::default())
+            .insert_resource(ParticlePool::default())
+            .add_systems(
+                Update,
+                log_pool_metrics
+                    .in_set(GameSet::Cleanup)
+                    .run_if(on_timer(Duration::from_secs_f32(POOL_METRICS_LOG_INTERVAL)))
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+impl<B: Bundle> Pool<B> {
+    /// Reactivates a parked entity with `bundle`, or spawns a new one when the
+    /// pool is empty.
+    pub fn acquire(&mut self, commands: &mut Commands, bundle: impl Bundle) -> Entity {
+        match self.free.pop() {
+            Some(entity) => {
+                self.parked.remove(&entity);
+                self.metrics.hits += 1;
+                commands
+                    .entity(entity)
+                    .remove::<Pooled>()
+                    .insert((bundle, Visibility::Inherited));
+                entity
+            }
+            None => {
+                self.metrics.misses += 1;
+                commands.spawn(bundle).id()
+            }
+        }
+    }
+
+    /// Parks `entity` instead of despawning it. Releasing an entity that is
+    /// already parked, e.g. a bullet that hit something on its last frame, is a no-op.
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if !self.parked.insert(entity) {
+            return;
+        }
+
+        self.metrics.releases += 1;
+        commands
+            .entity(entity)
+            .remove::<B>()
+            .insert((Pooled, Visibility::Hidden));
+        self.free.push(entity);
+        self.metrics.peak_free = self.metrics.peak_free.max(self.free.len());
+    }
+
+    pub fn free_count(&self) -> usize {
+        self.free.len()
+    }
+}
+
+impl PoolMetrics {
+    pub fn hit_rate(&self) -> f32 {
+        let acquires = self.hits + self.misses;
+        if acquires == 0 {
+            return 0.0;
+        }
+
+        self.hits as f32 / acquires as f32
+    }
+}
+
+fn log_pool_metrics(
+    bullet_pool: Res<BulletPool>,
+    enemy_pool: Res<EnemyPool>,
+    particle_pool: Res<ParticlePool>,
+) {
+    for (name, metrics, free) in [
+        ("bullet", bullet_pool.metrics, bullet_pool.free_count()),
+        ("enemy", enemy_pool.metrics, enemy_pool.free_count()),
+        (
+            "particle",
+            particle_pool.metrics,
+            particle_pool.free_count(),
+        ),
+    ] {
+        info!(
+            "{name} pool: hit rate {:.1}% ({} hits, {} misses), {} releases, {free} free, peak {}",
+            metrics.hit_rate() * 100.0,
+            metrics.hits,
+            metrics.misses,
+            metrics.releases,
+            metrics.peak_free,
+        );
+    }
+}
+
+impl<B: Bundle> Default for Pool<B> {
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            parked: EntityHashSet::default(),
+            metrics: PoolMetrics::default(),
+            marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{
+    console::{console_arg, ConsoleAppExt, ConsoleArgs, ConsoleResult},
+    events::EnemyKilled,
+    *,
+};
+
+pub struct ProgressionPlugin;
+
+/// Score and kills for the current run.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub score: u32,
+    pub kills: u32,
+}
+
+/// The current wave. Waves advance on a timer, each one spawning enemies faster.
+#[derive(Resource)]
+pub struct Wave {
+    pub number: u32,
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct Experience {
+    pub level: u32,
+    /// Experience gained towards the next level.
+    pub xp: u32,
+}
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<Wave>()
+            .add_console_command("wave", "wave <n>", &[], wave_command)
+            .add_systems(
+                Update,
+                (advance_wave, record_kills)
+                    .in_set(GameSet::Cleanup)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+impl Wave {
+    pub fn start(&mut self, number: u32) {
+        self.number = number.max(1);
+        self.timer.reset();
+    }
+
+    /// Enemies spawned per spawn tick during this wave.
+    pub fn spawn_rate(&self, tuning: &Tuning) -> u32 {
+        (self.number - 1)
+            .saturating_mul(tuning.wave_spawn_rate_step)
+            .saturating_add(tuning.spawn_rate)
+    }
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self {
+            number: 1,
+            timer: Timer::from_seconds(WAVE_DURATION_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self { level: 1, xp: 0 }
+    }
+}
+
+impl Experience {
+    pub fn next_level_xp(&self) -> u32 {
+        XP_FIRST_LEVEL + (self.level - 1) * XP_PER_LEVEL
+    }
+
+    /// Adds `xp` and returns how many levels were gained.
+    pub fn gain(&mut self, xp: u32) -> u32 {
+        self.xp += xp;
+        let mut levels = 0;
+        while self.xp >= self.next_level_xp() {
+            self.xp -= self.next_level_xp();
+            self.level += 1;
+            levels += 1;
+        }
+        levels
+    }
+}
+
+fn advance_wave(time: Res<Time>, mut wave: ResMut<Wave>) {
+    let finished = wave.timer.tick(time.delta()).times_finished_this_tick();
+    wave.number = wave.number.saturating_add(finished);
+}
+
+/// Experience comes from collecting the gems kills drop, see `pickups`.
+fn record_kills(mut stats: ResMut<RunStats>, mut killed_events: EventReader<EnemyKilled>) {
+    for killed in killed_events.read() {
+        stats.kills += 1;
+        stats.score += killed.kind.xp() * SCORE_PER_XP;
+    }
+}
+
+fn wave_command(In(args): In<ConsoleArgs>, mut wave: ResMut<Wave>) -> ConsoleResult {
+    let number = console_arg(&args, 0, wave.number)?;
+    wave.start(number);
+    Ok(format!("wave = {}", wave.number))
+}
@@ -1,33 +1,144 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::*;
+use crate::{
+    console::{ConsoleAppExt, ConsoleVars},
+    *,
+};
 
 //resources
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GlobalTextureAtlas {
     pub layout: Option<Handle<TextureAtlasLayout>>,
     pub image: Option<Handle<Image>>,
 }
 
+#[derive(Resource, Default)]
+pub struct GlobalFont(pub Handle<Font>);
+
 #[derive(Resource)]
 pub struct CursorPosition(pub Option<Vec2>);
 
+/// Balance values read at runtime, so they can be tried out with `set` instead
+/// of recompiling. Defaults come from `constants.rs`.
+#[derive(Resource)]
+pub struct Tuning {
+    pub player_speed: f32,
+    pub enemy_speed: f32,
+    pub enemy_contact_damage: f32,
+    pub bullet_speed: f32,
+    pub bullet_damage: f32,
+    pub bullet_knockback: f32,
+    pub fire_interval: f32,
+    pub bullets_per_shot: u32,
+    pub spawn_rate: u32,
+    pub wave_spawn_rate_step: u32,
+}
+
 pub struct ResourcesPlugin;
 
 impl Plugin for ResourcesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GlobalTextureAtlas::default())
+            .insert_resource(GlobalFont::default())
             .insert_resource(CursorPosition(None))
+            .init_resource::<Tuning>()
+            .add_console_vars::<Tuning>(&Tuning::VARS)
             .add_systems(OnEnter(GameState::Loading), load_assets)
             .add_systems(
                 Update,
-                update_cursor_position.run_if(in_state(GameState::InGame)),
+                update_cursor_position
+                    .in_set(GameSet::Input)
+                    .run_if(in_state(GameState::InGame)),
             );
     }
 }
 
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            player_speed: PLAYER_SPEED,
+            enemy_speed: ENEMY_SPEED,
+            enemy_contact_damage: ENEMY_CONTACT_DAMAGE,
+            bullet_speed: BULLET_SPEED,
+            bullet_damage: BULLET_DAMAGE,
+            bullet_knockback: BULLET_KNOCKBACK,
+            fire_interval: BULLET_SPAWN_INTERVAL,
+            bullets_per_shot: NUM_OF_BULLET_PER_SHOT,
+            spawn_rate: SPAWN_RATE_PER_SECOND,
+            wave_spawn_rate_step: WAVE_SPAWN_RATE_STEP,
+        }
+    }
+}
+
+impl Tuning {
+    pub const VARS: [&'static str; 10] = [
+        "player_speed",
+        "enemy_speed",
+        "enemy_contact_damage",
+        "bullet_speed",
+        "bullet_damage",
+        "bullet_knockback",
+        "fire_interval",
+        "bullets_per_shot",
+        "spawn_rate",
+        "wave_spawn_rate_step",
+    ];
+}
+
+impl ConsoleVars for Tuning {
+    fn get_var(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "player_speed" => self.player_speed.to_string(),
+            "enemy_speed" => self.enemy_speed.to_string(),
+            "enemy_contact_damage" => self.enemy_contact_damage.to_string(),
+            "bullet_speed" => self.bullet_speed.to_string(),
+            "bullet_damage" => self.bullet_damage.to_string(),
+            "bullet_knockback" => self.bullet_knockback.to_string(),
+            "fire_interval" => self.fire_interval.to_string(),
+            "bullets_per_shot" => self.bullets_per_shot.to_string(),
+            "spawn_rate" => self.spawn_rate.to_string(),
+            "wave_spawn_rate_step" => self.wave_spawn_rate_step.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn set_var(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value for {name}: {value}");
+        let amount = |value: &str| {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|amount| *amount >= 0.0)
+                .ok_or_else(invalid)
+        };
+        let count = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+
+        match name {
+            "player_speed" => self.player_speed = amount(value)?,
+            "enemy_speed" => self.enemy_speed = amount(value)?,
+            "enemy_contact_damage" => self.enemy_contact_damage = amount(value)?,
+            "bullet_speed" => self.bullet_speed = amount(value)?,
+            "bullet_damage" => self.bullet_damage = amount(value)?,
+            "bullet_knockback" => self.bullet_knockback = amount(value)?,
+            // The cooldown bar divides by it
+            "fire_interval" => {
+                self.fire_interval = Some(amount(value)?)
+                    .filter(|interval| *interval > 0.0)
+                    .ok_or_else(invalid)?
+            }
+            "bullets_per_shot" => self.bullets_per_shot = count(value)?,
+            "spawn_rate" => self.spawn_rate = count(value)?,
+            "wave_spawn_rate_step" => self.wave_spawn_rate_step = count(value)?,
+            _ => return Err(format!("unknown var: {name}")),
+        }
+        Ok(())
+    }
+}
+
 fn load_assets(
     mut handle: ResMut<GlobalTextureAtlas>,
+    mut font: ResMut<GlobalFont>,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut next_state: ResMut<NextState<GameState>>,
@@ -41,7 +152,8 @@ fn load_assets(
         None,
     );
     handle.layout = Some(texture_atlas_layouts.add(layout));
-    next_state.set(GameState::GameInit);
+    font.0 = asset_server.load(FONT_PATH);
+    next_state.set(GameState::MainMenu);
 }
 
 fn update_cursor_position(
@@ -61,12 +173,3 @@ fn update_cursor_position(
         .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
         .map(|ray| ray.origin.truncate());
 }
-
-impl Default for GlobalTextureAtlas {
-    fn default() -> Self {
-        Self {
-            layout: None,
-            image: None,
-        }
-    }
-}
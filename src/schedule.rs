@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+pub struct SchedulePlugin;
+
+/// Stages of a gameplay frame in `Update`, run in declaration order. Every
+/// gameplay system belongs to one of these so a frame never sees damage
+/// before the hit that caused it.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum GameSet {
+    /// Reading player input and the cursor, firing.
+    Input,
+    /// Moving the player, enemies, bullets and the gun.
+    Movement,
+    /// Rebuilding the broad phase and turning overlaps into collision and hit events.
+    Collision,
+    /// Applying hit and contact events to health.
+    Damage,
+    /// Entity lifecycle: despawning or pooling dead and expired entities, spawning new ones.
+    Cleanup,
+    /// Sprite frames, flipping and other visuals that follow from the final positions.
+    Animation,
+    Camera,
+    Ui,
+}
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (
+                GameSet::Input,
+                GameSet::Movement,
+                GameSet::Collision,
+                GameSet::Damage,
+                GameSet::Cleanup,
+                GameSet::Animation,
+                GameSet::Camera,
+                GameSet::Ui,
+            )
+                .chain(),
+        );
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    fmt::Write as _,
+    fs, thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::*,
+    window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode},
+};
+
+use crate::{
+    console::{ConsoleAppExt, ConsoleVars},
+    *,
+};
+
+pub struct SettingsPlugin;
+
+/// Player-facing options, loaded from [`SETTINGS_PATH`] at startup and saved
+/// when the settings screen is closed.
+#[derive(Resource)]
+pub struct Settings {
+    pub damage_numbers: bool,
+    /// Scales camera shake, 0 turns it off.
+    pub screen_shake: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub display_mode: DisplayMode,
+    /// Window size in logical pixels, used in windowed mode.
+    pub resolution: UVec2,
+    pub vsync: bool,
+    /// Frames per second to stay under, 0 for no limit.
+    pub fps_cap: u32,
+    pub keys: Keybindings,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+/// Rebindable keys. The arrow keys always move as well.
+pub struct Keybindings {
+    pub move_up: KeyCode,
+    pub move_down: KeyCode,
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub reload: KeyCode,
+    pub dash: KeyCode,
+}
+
+/// An input that can be rebound on the settings screen.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Reload,
+    Dash,
+}
+
+/// Keys offered for binding, named as in the config file.
+const BINDABLE_KEYS: [KeyCode; 52] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::ArrowUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Enter,
+    KeyCode::CapsLock,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Comma,
+    KeyCode::Period,
+];
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // `main` may have loaded them already to open the window at the saved size
+        if !app.world().contains_resource::<Settings>() {
+            app.insert_resource(Settings::load());
+        }
+        app.add_console_vars::<Settings>(&[
+                "damage_numbers",
+                "screen_shake",
+                "master_volume",
+                "music_volume",
+                "sfx_volume",
+                "fps_cap",
+            ])
+            .add_systems(
+                Update,
+                apply_window_settings.run_if(resource_changed::<Settings>),
+            )
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            damage_numbers: true,
+            screen_shake: 1.0,
+            master_volume: 1.0,
+            music_volume: 0.5,
+            sfx_volume: 0.8,
+            display_mode: DisplayMode::default(),
+            resolution: UVec2::new(WW as u32, WH as u32),
+            vsync: true,
+            fps_cap: 0,
+            keys: Keybindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Music volume with the master volume applied.
+    pub fn mixed_music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    /// Sound effect volume with the master volume applied.
+    pub fn mixed_sfx_volume(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    /// Reads the config file, keeping defaults for anything missing or invalid.
+    pub fn load() -> Self {
+        let mut settings = Self::default();
+        let Ok(config) = fs::read_to_string(SETTINGS_PATH) else {
+            return settings;
+        };
+
+        for line in config.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("{SETTINGS_PATH}: expected key=value, got {line:?}");
+                continue;
+            };
+            if let Err(err) = settings.set(key.trim(), value.trim()) {
+                warn!("{SETTINGS_PATH}: {err}");
+            }
+        }
+        settings
+    }
+
+    /// The primary window these settings describe, so the game opens as it was left.
+    pub fn window(&self) -> Window {
+        Window {
+            resizable: true,
+            focused: true,
+            mode: self.display_mode.window_mode(),
+            present_mode: self.present_mode(),
+            resolution: self.resolution.as_vec2().into(),
+            ..default()
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    pub fn save(&self) {
+        let mut config = String::new();
+        for (key, value) in self.entries() {
+            let _ = writeln!(config, "{key}={value}");
+        }
+        if let Err(err) = fs::write(SETTINGS_PATH, config) {
+            warn!("could not save {SETTINGS_PATH}: {err}");
+        }
+    }
+
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![
+            ("damage_numbers", self.damage_numbers.to_string()),
+            ("screen_shake", self.screen_shake.to_string()),
+            ("master_volume", self.master_volume.to_string()),
+            ("music_volume", self.music_volume.to_string()),
+            ("sfx_volume", self.sfx_volume.to_string()),
+            ("display_mode", self.display_mode.name().to_string()),
+            (
+                "resolution",
+                format!("{}x{}", self.resolution.x, self.resolution.y),
+            ),
+            ("vsync", self.vsync.to_string()),
+            ("fps_cap", self.fps_cap.to_string()),
+        ];
+        entries.extend(
+            Action::ALL.map(|action| (action.config_key(), key_name(self.keys.get(action)))),
+        );
+        entries
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value for {key}: {value}");
+        let volume = |value: &str| {
+            value
+                .parse::<f32>()
+                .map(|volume| volume.clamp(0.0, 1.0))
+                .map_err(|_| invalid())
+        };
+
+        match key {
+            "damage_numbers" => self.damage_numbers = value.parse().map_err(|_| invalid())?,
+            "screen_shake" => self.screen_shake = volume(value)?,
+            "master_volume" => self.master_volume = volume(value)?,
+            "music_volume" => self.music_volume = volume(value)?,
+            "sfx_volume" => self.sfx_volume = volume(value)?,
+            "display_mode" => {
+                self.display_mode = DisplayMode::ALL
+                    .into_iter()
+                    .find(|mode| mode.name() == value)
+                    .ok_or_else(invalid)?
+            }
+            "resolution" => {
+                let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+                self.resolution = UVec2::new(
+                    width.parse().map_err(|_| invalid())?,
+                    height.parse().map_err(|_| invalid())?,
+                );
+            }
+            "vsync" => self.vsync = value.parse().map_err(|_| invalid())?,
+            "fps_cap" => self.fps_cap = value.parse().map_err(|_| invalid())?,
+            _ => {
+                let action = Action::ALL
+                    .into_iter()
+                    .find(|action| action.config_key() == key)
+                    .ok_or_else(|| format!("unknown setting: {key}"))?;
+                self.keys.bind(action, parse_key(value).ok_or_else(invalid)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ConsoleVars for Settings {
+    fn get_var(&self, name: &str) -> Option<String> {
+        self.entries()
+            .into_iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Same parsing and clamping as the config file.
+    fn set_var(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.set(name, value)
+    }
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "windowed",
+            DisplayMode::Borderless => "borderless",
+            DisplayMode::Fullscreen => "fullscreen",
+        }
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        }
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self {
+            move_up: KeyCode::KeyW,
+            move_down: KeyCode::KeyS,
+            move_left: KeyCode::KeyA,
+            move_right: KeyCode::KeyD,
+            reload: KeyCode::KeyR,
+            dash: KeyCode::Space,
+        }
+    }
+}
+
+impl Keybindings {
+    pub fn get(&self, action: Action) -> KeyCode {
+        match action {
+            Action::MoveUp => self.move_up,
+            Action::MoveDown => self.move_down,
+            Action::MoveLeft => self.move_left,
+            Action::MoveRight => self.move_right,
+            Action::Reload => self.reload,
+            Action::Dash => self.dash,
+        }
+    }
+
+    /// Binds `key` to `action`. Whatever action had `key` before takes over
+    /// `action`'s old key, so no two actions share one.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        let previous = self.get(action);
+        if let Some(other) = Action::ALL
+            .into_iter()
+            .find(|other| *other != action && self.get(*other) == key)
+        {
+            *self.get_mut(other) = previous;
+        }
+        *self.get_mut(action) = key;
+    }
+
+    pub fn get_mut(&mut self, action: Action) -> &mut KeyCode {
+        match action {
+            Action::MoveUp => &mut self.move_up,
+            Action::MoveDown => &mut self.move_down,
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Reload => &mut self.reload,
+            Action::Dash => &mut self.dash,
+        }
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Reload,
+        Action::Dash,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Reload => "Reload",
+            Action::Dash => "Dash",
+        }
+    }
+
+    fn config_key(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "key_move_up",
+            Action::MoveDown => "key_move_down",
+            Action::MoveLeft => "key_move_left",
+            Action::MoveRight => "key_move_right",
+            Action::Reload => "key_reload",
+            Action::Dash => "key_dash",
+        }
+    }
+}
+
+/// Name of `key` in the config file and on the settings screen.
+pub fn key_name(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+/// The bindable key called `name`, if there is one.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|key| key_name(*key) == name)
+}
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    // Only assign what differs, so the window isn't rebuilt on every change
+    let mode = settings.display_mode.window_mode();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let present_mode = settings.present_mode();
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+    let resolution = settings.resolution.as_vec2();
+    if settings.display_mode == DisplayMode::Windowed && window.resolution.size() != resolution {
+        window.resolution.set(resolution.x, resolution.y);
+    }
+}
+
+/// Sleeps off whatever is left of the frame budget when the frame rate is capped.
+fn limit_frame_rate(settings: Res<Settings>, mut frame_start: Local<Option<Instant>>) {
+    if let (Some(start), true) = (*frame_start, settings.fps_cap > 0) {
+        let budget = Duration::from_secs_f32(1.0 / settings.fps_cap as f32);
+        if let Some(remaining) = budget.checked_sub(start.elapsed()) {
+            thread::sleep(remaining);
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
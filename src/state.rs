@@ -5,6 +5,8 @@ use bevy::prelude::*;
 pub enum GameState {
     #[default]
     Loading,
+    MainMenu,
     GameInit,
     InGame,
+    Paused,
 }
@@ -1,10 +1,19 @@
 use crate::{
     animation::AnimationTimer,
-    gun::{Gun, GunTimer},
-    player::{Player, PlayerState},
+    biome::BiomeMap,
+    collision::{Collider, CollisionLayers},
+    dash::Dash,
+    gun::{Ammo, Gun, GunTimer},
+    pickups::PowerUps,
+    player::{Health, Player, PlayerState},
+    progression::Experience,
     *,
 };
-use bevy::{math::vec3, prelude::*, time::Stopwatch};
+use bevy::{
+    math::{vec2, vec3},
+    prelude::*,
+    time::Stopwatch,
+};
 use rand::Rng;
 
 pub struct WorldPlugin;
@@ -35,6 +44,17 @@ fn init_world(
         AnimationTimer(Timer::from_seconds(0.15, TimerMode::Repeating)),
         Player,
         PlayerState::default(),
+        Health {
+            current: PLAYER_HEALTH,
+            max: PLAYER_HEALTH,
+        },
+        Experience::default(),
+        PowerUps::default(),
+        Dash::default(),
+        Collider::circle(PLAYER_COLLIDER_RADIUS).with_layers(
+            CollisionLayers::PLAYER,
+            CollisionLayers::ENEMY | CollisionLayers::PICKUP | CollisionLayers::OBSTACLE,
+        ),
     ));
     commands.spawn((
         Sprite::from_atlas_image(
@@ -47,21 +67,27 @@ fn init_world(
         Transform::from_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
         Gun,
         GunTimer(Stopwatch::new()),
+        Ammo::shotgun(),
     ));
     next_state.set(GameState::InGame);
 }
 
-fn spawn_world_decoration(mut commands: Commands, handle: Res<GlobalTextureAtlas>) {
+fn spawn_world_decoration(
+    mut commands: Commands,
+    handle: Res<GlobalTextureAtlas>,
+    biome_map: Res<BiomeMap>,
+) {
     let mut rng = rand::rng();
     for _ in 0..NUM_WORLD_DECORATIONS {
         let x = rng.random_range(-WORLD_W..WORLD_W);
         let y = rng.random_range(-WORLD_H..WORLD_H);
+        let biome = biome_map.biome_at(vec2(x, y));
         commands.spawn((
             Sprite::from_atlas_image(
                 handle.image.clone().unwrap(),
                 TextureAtlas {
                     layout: handle.layout.clone().unwrap(),
-                    index: rng.random_range(24..=25),
+                    index: biome.def().random_decoration(&mut rng),
                 },
             ),
             Transform::from_translation(vec3(x, y, 0.0))
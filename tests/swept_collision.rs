@@ -0,0 +1,180 @@
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use z_attack_game::collision::{
+    sweep_circle, Collider, ColliderShape, CollisionLayers, CollisionPlugin, PreviousPosition,
+};
+use z_attack_game::enemy::Enemy;
+use z_attack_game::events::{BulletHit, GameEventsPlugin};
+use z_attack_game::gun::{Bullet, BulletDirection, Shooter};
+use z_attack_game::pool::BulletPool;
+use z_attack_game::*;
+
+const BULLET_RADIUS: f32 = 10.5;
+
+#[test]
+fn fast_bullet_hits_thin_circle_it_passes_through() {
+    let target = ColliderShape::Circle { radius: 1.0 };
+    // 2000 units in a single step, far more than the target is wide
+    let t = sweep_circle(
+        vec2(-1000.0, 0.0),
+        vec2(1000.0, 0.0),
+        BULLET_RADIUS,
+        &target,
+        Vec2::ZERO,
+    )
+    .expect("bullet tunnelled through the target");
+
+    let hit_x = -1000.0 + 2000.0 * t;
+    assert!((hit_x - -(BULLET_RADIUS + 1.0)).abs() < 1e-3);
+}
+
+#[test]
+fn fast_bullet_hits_thin_wall() {
+    let wall = ColliderShape::Aabb {
+        half_extents: vec2(0.5, 100.0),
+    };
+    let t = sweep_circle(
+        vec2(-500.0, 40.0),
+        vec2(500.0, 40.0),
+        BULLET_RADIUS,
+        &wall,
+        Vec2::ZERO,
+    )
+    .expect("bullet tunnelled through the wall");
+
+    let hit_x = -500.0 + 1000.0 * t;
+    assert!((hit_x - -(BULLET_RADIUS + 0.5)).abs() < 1e-3);
+}
+
+#[test]
+fn earliest_of_several_targets_is_the_closest_to_the_start() {
+    let mut app = bullet_app();
+    let targets = [vec2(2500.0, 0.0), vec2(400.0, 5.0), vec2(1200.0, -3.0)]
+        .map(|pos| spawn_enemy(&mut app, pos));
+    let shooter = app.world_mut().spawn_empty().id();
+    fire(&mut app, shooter, vec2(0.0, 0.0), vec2(3000.0, 0.0));
+
+    app.update();
+
+    let hits = drain_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].target, targets[1]);
+}
+
+#[test]
+fn targets_hit_at_the_same_point_go_to_the_lower_entity() {
+    let mut app = bullet_app();
+    let first = spawn_enemy(&mut app, vec2(500.0, 0.0));
+    let second = spawn_enemy(&mut app, vec2(500.0, 0.0));
+    let shooter = app.world_mut().spawn_empty().id();
+    fire(&mut app, shooter, vec2(0.0, 0.0), vec2(1000.0, 0.0));
+
+    app.update();
+
+    let hits = drain_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].target, first.min(second));
+}
+
+#[test]
+fn bullet_passing_beside_target_misses() {
+    let target = ColliderShape::Circle { radius: 1.0 };
+    let t = sweep_circle(
+        vec2(-1000.0, 20.0),
+        vec2(1000.0, 20.0),
+        BULLET_RADIUS,
+        &target,
+        Vec2::ZERO,
+    );
+
+    assert_eq!(t, None);
+}
+
+#[test]
+fn bullet_stopping_short_of_target_misses() {
+    let target = ColliderShape::Circle { radius: 1.0 };
+    let t = sweep_circle(
+        vec2(-100.0, 0.0),
+        vec2(-50.0, 0.0),
+        BULLET_RADIUS,
+        &target,
+        Vec2::ZERO,
+    );
+
+    assert_eq!(t, None);
+}
+
+#[test]
+fn bullet_moving_away_from_target_misses() {
+    let wall = ColliderShape::Aabb {
+        half_extents: vec2(0.5, 100.0),
+    };
+    let t = sweep_circle(
+        vec2(50.0, 0.0),
+        vec2(1000.0, 0.0),
+        BULLET_RADIUS,
+        &wall,
+        Vec2::ZERO,
+    );
+
+    assert_eq!(t, None);
+}
+
+#[test]
+fn bullet_moving_a_whole_screen_per_frame_hits_enemy_it_skips_over() {
+    let mut app = bullet_app();
+    let enemy = spawn_enemy(&mut app, Vec2::ZERO);
+    let shooter = app.world_mut().spawn_empty().id();
+    // Neither end of the step overlaps the enemy, only the path between them does
+    fire(&mut app, shooter, vec2(-1200.0, 0.0), vec2(1200.0, 0.0));
+
+    app.update();
+
+    let hits = drain_hits(&mut app);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].target, enemy);
+    assert_eq!(hits[0].shooter, shooter);
+    assert!(hits[0].position.x < 0.0);
+}
+
+fn bullet_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .insert_state(GameState::InGame)
+        .insert_resource(BulletPool::default())
+        .init_resource::<Tuning>()
+        .add_plugins((GameEventsPlugin, CollisionPlugin));
+    app
+}
+
+fn spawn_enemy(app: &mut App, pos: Vec2) -> Entity {
+    app.world_mut()
+        .spawn((
+            Transform::from_translation(pos.extend(10.0)),
+            Enemy::default(),
+            Collider::circle(ENEMY_COLLIDER_RADIUS)
+                .with_layers(CollisionLayers::ENEMY, CollisionLayers::BULLET),
+        ))
+        .id()
+}
+
+/// A bullet that moved from `from` to `to` in the last frame.
+fn fire(app: &mut App, shooter: Entity, from: Vec2, to: Vec2) {
+    app.world_mut().spawn((
+        Transform::from_translation(to.extend(10.0)),
+        PreviousPosition(from),
+        Bullet,
+        BulletDirection((to - from).normalize().extend(0.0)),
+        Shooter(shooter),
+        Collider::circle(BULLET_COLLIDER_RADIUS)
+            .with_layers(CollisionLayers::BULLET, CollisionLayers::ENEMY),
+    ));
+}
+
+fn drain_hits(app: &mut App) -> Vec<BulletHit> {
+    app.world_mut()
+        .resource_mut::<Events<BulletHit>>()
+        .drain()
+        .collect()
+}
@@ -0,0 +1,87 @@
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use z_attack_game::biome::BiomeMap;
+use z_attack_game::collision::{Collider, CollisionLayers, CollisionPlugin, PreviousPosition};
+use z_attack_game::enemy::{Enemy, EnemyPlugin};
+use z_attack_game::events::{EnemyKilled, GameEventsPlugin};
+use z_attack_game::gun::{Bullet, BulletDirection, Shooter};
+use z_attack_game::pool::BulletPool;
+use z_attack_game::{
+    GameSet, GameState, SchedulePlugin, Tuning, BULLET_COLLIDER_RADIUS, ENEMY_COLLIDER_RADIUS,
+};
+
+#[derive(Resource, Default)]
+struct RunOrder(Vec<GameSet>);
+
+fn record(set: GameSet) -> impl FnMut(ResMut<RunOrder>) {
+    move |mut order: ResMut<RunOrder>| order.0.push(set)
+}
+
+#[test]
+fn game_sets_run_in_declared_order() {
+    let sets = [
+        GameSet::Input,
+        GameSet::Movement,
+        GameSet::Collision,
+        GameSet::Damage,
+        GameSet::Cleanup,
+        GameSet::Animation,
+        GameSet::Camera,
+        GameSet::Ui,
+    ];
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, SchedulePlugin))
+        .init_resource::<RunOrder>();
+    // Registered back to front so insertion order can't be what makes this pass
+    for set in sets.iter().rev() {
+        app.add_systems(Update, record(*set).in_set(*set));
+    }
+
+    app.update();
+
+    assert_eq!(app.world().resource::<RunOrder>().0, sets);
+}
+
+#[test]
+fn bullet_hit_kills_enemy_in_the_same_frame() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, SchedulePlugin))
+        .insert_state(GameState::InGame)
+        .insert_resource(BulletPool::default())
+        .init_resource::<Tuning>()
+        .insert_resource(BiomeMap::new(0))
+        .add_plugins((GameEventsPlugin, CollisionPlugin, EnemyPlugin));
+
+    app.world_mut().spawn((
+        Transform::default(),
+        Enemy {
+            health: 0.1,
+            ..default()
+        },
+        Collider::circle(ENEMY_COLLIDER_RADIUS)
+            .with_layers(CollisionLayers::ENEMY, CollisionLayers::BULLET),
+    ));
+    let shooter = app.world_mut().spawn_empty().id();
+    app.world_mut().spawn((
+        Transform::from_xyz(100.0, 0.0, 10.0),
+        PreviousPosition(vec2(-100.0, 0.0)),
+        Bullet,
+        BulletDirection(Vec3::X),
+        Shooter(shooter),
+        Collider::circle(BULLET_COLLIDER_RADIUS)
+            .with_layers(CollisionLayers::BULLET, CollisionLayers::ENEMY),
+    ));
+
+    // handle_bullet_hits -> apply_bullet_hits -> kill_dead_enemies, all in one update
+    app.update();
+
+    let killed: Vec<EnemyKilled> = app
+        .world_mut()
+        .resource_mut::<Events<EnemyKilled>>()
+        .drain()
+        .collect();
+    assert_eq!(killed.len(), 1);
+    assert_eq!(killed[0].killer, Some(shooter));
+}
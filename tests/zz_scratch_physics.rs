@@ -0,0 +1,33 @@
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use z_attack_game::collision::{Collider, CollisionLayers, CollisionPlugin, PreviousPosition};
+use z_attack_game::enemy::Enemy;
+use z_attack_game::events::{BulletHit, GameEventsPlugin};
+use z_attack_game::gun::{Bullet, BulletDirection, Shooter};
+use z_attack_game::pool::BulletPool;
+use z_attack_game::*;
+
+#[test]
+fn scratch() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, TransformPlugin, AssetPlugin::default(), bevy::scene::ScenePlugin))
+        .init_asset::<Mesh>()
+        .insert_state(GameState::InGame)
+        .insert_resource(BulletPool::default())
+        .init_resource::<Tuning>()
+        .add_plugins((GameEventsPlugin, CollisionPlugin));
+    let enemy = app.world_mut().spawn((Transform::default(), Enemy::default(),
+        Collider::circle(ENEMY_COLLIDER_RADIUS).with_layers(CollisionLayers::ENEMY, CollisionLayers::BULLET))).id();
+    app.update();
+    app.update();
+    let shooter = app.world_mut().spawn_empty().id();
+    app.world_mut().spawn((Transform::from_xyz(1200.0, 0.0, 10.0), PreviousPosition(vec2(-1200.0, 0.0)),
+        Bullet, BulletDirection(Vec3::X), Shooter(shooter),
+        Collider::circle(BULLET_COLLIDER_RADIUS).with_layers(CollisionLayers::BULLET, CollisionLayers::ENEMY)));
+    app.update();
+    let hits: Vec<BulletHit> = app.world_mut().resource_mut::<Events<BulletHit>>().drain().collect();
+    println!("{hits:?}");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].target, enemy);
+}